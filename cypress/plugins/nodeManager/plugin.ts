@@ -346,6 +346,7 @@ class NodeManager implements NodeManagerPlugin {
           hints: { showRemoteHelper: true },
           uiFont: "inter",
           codeFont: "sourceCode",
+          codeTheme: null,
         },
         coco: {
           seeds: remainingNodes.map(node => node.peerAddress),
@@ -4,16 +4,82 @@
 // with Radicle Linking Exception. For full terms see the included
 // LICENSE file.
 
-use std::convert::TryFrom as _;
+use std::{
+    collections::HashMap,
+    convert::TryFrom as _,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
+use lazy_static::lazy_static;
 use librad::git::types::{Reference, Single};
-use link_crypto::BoxedSigner;
-use radicle_source::{error, surf::vcs::git};
+use link_crypto::{BoxedSigner, PeerId};
+use radicle_source::{
+    error,
+    surf::vcs::git::{self, git2},
+};
 
 use crate::error::Error;
 
+/// Maximum number of idle handles kept open per repository.
+const MAX_IDLE: usize = 8;
+
+lazy_static! {
+    /// Idle handles to the monorepo used to build a [`git::Browser`] in [`using`].
+    static ref BROWSER_REPOSITORIES: Pool<git::Repository> = Pool::default();
+    /// Idle handles to the monorepo for [`using_repository`]. They are kept apart from the ones
+    /// for [`using`], which switches the namespace of its handles.
+    static ref REPOSITORIES: Pool<git2::Repository> = Pool::default();
+}
+
+/// Repository handles that were opened before and are not in use, by path of the repository.
+/// Reusing them saves reading the configuration and the packs of the repository on every request.
+struct Pool<R> {
+    /// The idle handles.
+    idle: Mutex<HashMap<PathBuf, Vec<R>>>,
+}
+
+impl<R> Default for Pool<R> {
+    fn default() -> Self {
+        Self {
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<R> Pool<R> {
+    /// Run `callback` with an idle handle to the repository at `path`, or a new one opened with
+    /// `open` if there is none. The handle is returned to the pool afterwards.
+    fn with<T, O, F>(&self, path: &Path, open: O, callback: F) -> Result<T, Error>
+    where
+        O: FnOnce(&Path) -> Result<R, Error>,
+        F: FnOnce(&R) -> Result<T, Error>,
+    {
+        let idle = self
+            .idle
+            .lock()
+            .expect("repository pool lock poisoned")
+            .get_mut(path)
+            .and_then(Vec::pop);
+        let repo = match idle {
+            Some(repo) => repo,
+            None => open(path)?,
+        };
+
+        let result = callback(&repo);
+
+        let mut idle = self.idle.lock().expect("repository pool lock poisoned");
+        let handles = idle.entry(path.to_path_buf()).or_default();
+        if handles.len() < MAX_IDLE {
+            handles.push(repo);
+        }
+
+        result
+    }
+}
+
 /// Provide a repo [`git::Browser`] where the `Browser` is initialised with the provided
-/// `reference`.
+/// `reference`. The monorepo handle backing it is taken from a pool of idle handles.
 ///
 /// See [`radicle_daemon::state::find_default_branch`] and [`radicle_daemon::state::get_branch`] for
 /// obtaining a [`Reference`].
@@ -49,9 +115,52 @@ where
     };
 
     let monorepo = radicle_daemon::state::monorepo(peer);
-    let repo = git::Repository::new(monorepo).map_err(error::Error::from)?;
-    let mut browser =
-        git::Browser::new_with_namespace(&repo, &namespace, branch).map_err(error::Error::from)?;
+    BROWSER_REPOSITORIES.with(
+        &monorepo,
+        |path| Ok(git::Repository::new(path).map_err(error::Error::from)?),
+        |repo| {
+            let mut browser = git::Browser::new_with_namespace(repo, &namespace, branch)
+                .map_err(error::Error::from)?;
+
+            Ok(callback(&mut browser)?)
+        },
+    )
+}
+
+/// Provide the monorepo as a raw [`git2::Repository`] for operations that [`git::Browser`] does
+/// not cover, e.g. revision walks over object ids obtained through [`resolve`]. The handle is
+/// taken from a pool of idle handles.
+///
+/// # Errors
+///   * If we could not open the backing storage.
+///   * If the callback provided returned an error.
+pub fn using_repository<T, F>(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    callback: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(&git2::Repository) -> Result<T, Error> + Send,
+{
+    let monorepo = radicle_daemon::state::monorepo(peer);
+    REPOSITORIES.with(
+        &monorepo,
+        |path| Ok(git2::Repository::open_bare(path)?),
+        callback,
+    )
+}
+
+/// Resolve `revision` to the id of the commit it points to. If no `revision` is provided the
+/// head of the history the `browser` was initialised with is used.
+///
+/// # Errors
+///   * If the `revision` could not be found in the namespace of the `browser`.
+pub fn resolve(
+    browser: &mut git::Browser,
+    revision: Option<radicle_source::Revision<PeerId>>,
+) -> Result<git::Oid, radicle_source::Error> {
+    if let Some(revision) = revision {
+        browser.rev(git::Rev::try_from(revision)?)?;
+    }
 
-    Ok(callback(&mut browser)?)
+    Ok(browser.get().first().id)
 }
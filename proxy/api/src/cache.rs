@@ -0,0 +1,79 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Memoisation of results computed from immutable git objects.
+
+use std::{hash::Hash, sync::Mutex};
+
+use lru::LruCache;
+
+/// A bounded cache evicting the least recently used entries.
+///
+/// Keys must identify their value for good, i.e. be derived from object ids rather than from
+/// references, which can move.
+pub struct Cache<K: Hash + Eq, V> {
+    /// The cached values.
+    entries: Mutex<LruCache<K, V>>,
+}
+
+impl<K: Hash + Eq, V: Clone> Cache<K, V> {
+    /// Create an empty cache holding at most `capacity` entries.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Get the value for `key`, or compute it with `compute` and store it if it isn't cached yet.
+    /// Errors are passed through and not cached.
+    ///
+    /// The cache isn't locked while `compute` runs, so concurrent misses on the same key may
+    /// compute the value more than once.
+    ///
+    /// # Errors
+    ///   * If `compute` fails.
+    pub fn get_or_try_insert_with<E, F>(&self, key: K, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        if let Some(value) = self.entries.lock().expect("cache lock poisoned").get(&key) {
+            return Ok(value.clone());
+        }
+
+        let value = compute()?;
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .put(key, value.clone());
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::Cache;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = Cache::new(2);
+        let compute = |value: u32| move || Ok::<_, ()>(value);
+
+        assert_eq!(cache.get_or_try_insert_with("a", compute(1)), Ok(1));
+        assert_eq!(cache.get_or_try_insert_with("b", compute(2)), Ok(2));
+        // Hit, which makes `b` the least recently used entry.
+        assert_eq!(cache.get_or_try_insert_with("a", compute(10)), Ok(1));
+        assert_eq!(cache.get_or_try_insert_with("c", compute(3)), Ok(3));
+
+        assert_eq!(cache.get_or_try_insert_with("a", compute(10)), Ok(1));
+        assert_eq!(cache.get_or_try_insert_with("b", compute(20)), Ok(20));
+        assert_eq!(cache.get_or_try_insert_with("d", || Err(())), Err(()));
+        assert_eq!(cache.get_or_try_insert_with("d", compute(4)), Ok(4));
+    }
+}
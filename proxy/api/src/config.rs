@@ -47,6 +47,12 @@ pub fn store_dir(profile_id: &ProfileId) -> path::PathBuf {
     store_root.join(profile_id.as_str()).join("store")
 }
 
+/// Returns the directory user provided syntax highlighting themes are loaded from.
+#[must_use]
+pub fn themes_dir() -> path::PathBuf {
+    dirs().config_dir().join("themes")
+}
+
 /// Returns the path to a folder containing helper binaries.
 ///
 /// # Errors
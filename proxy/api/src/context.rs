@@ -14,7 +14,7 @@ use tokio::sync::RwLock;
 
 use link_crypto::BoxedSigner;
 
-use crate::{keystore, service};
+use crate::{keystore, service, source};
 
 /// Container to pass down dependencies into HTTP filter chains.
 #[derive(Clone)]
@@ -174,6 +174,8 @@ pub struct Unsealed {
     pub keystore: Arc<dyn keystore::Keystore + Send + Sync>,
     /// Notification to shutdown the HTTP server
     pub shutdown: Arc<tokio::sync::Notify>,
+    /// Syntax highlighting themes, built-in ones and the ones in the themes directory.
+    pub themes: Arc<source::theme::Registry>,
 }
 
 /// Context for HTTP request if the coco peer APIs have not been initialized yet.
@@ -249,6 +251,9 @@ impl Unsealed {
                 auth_token: Arc::new(RwLock::new(None)),
                 keystore: Arc::new(keystore::memory()),
                 shutdown: Arc::new(tokio::sync::Notify::new()),
+                themes: Arc::new(source::theme::Registry::load(
+                    &tmp_dir.path().join("themes"),
+                )),
             },
             run_handle,
         ))
@@ -9,6 +9,8 @@
 use std::io;
 
 use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
 
 use crate::keystore;
 
@@ -26,6 +28,10 @@ pub enum Error {
     #[error(transparent)]
     Source(#[from] radicle_source::error::Error),
 
+    /// Error interacting with the monorepo directly through [`git2`].
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
     #[error(transparent)]
     Peer(#[from] radicle_daemon::peer::Error),
 
@@ -33,6 +39,10 @@ pub enum Error {
     #[error(transparent)]
     Io(#[from] io::Error),
 
+    /// A response could not be assembled.
+    #[error(transparent)]
+    Response(#[from] warp::http::Error),
+
     #[error("the current session is in use by `{0}`")]
     SessionInUse(Urn),
 
@@ -55,6 +65,87 @@ pub enum Error {
     #[error("project not found")]
     ProjectNotFound,
 
+    /// There is no entry at the requested path of a tree.
+    #[error("path `{0}` not found")]
+    PathNotFound(String),
+
+    /// A path supplied by the user leaves the tree it is relative to.
+    #[error("path `{0}` is invalid")]
+    InvalidPath(String),
+
+    /// There is no commit with the requested id.
+    #[error("commit `{0}` not found")]
+    CommitNotFound(String),
+
+    /// There is no patch with the requested id.
+    #[error("patch `{0}` not found")]
+    PatchNotFound(String),
+
+    /// The patch has no revision with the requested number.
+    #[error("revision `{revision}` of patch `{id}` not found")]
+    PatchRevisionNotFound {
+        /// Id of the patch.
+        id: String,
+        /// Number of the revision.
+        revision: usize,
+    },
+
+    /// There is no comment with the requested id on the patch.
+    #[error("comment `{0}` not found")]
+    CommentNotFound(String),
+
+    /// A comment needs a body.
+    #[error("the body of a comment must not be empty")]
+    EmptyComment,
+
+    /// The local peer already published a patch with the requested id.
+    #[error("patch `{0}` already exists")]
+    PatchExists(String),
+
+    /// The requested patch id can't be used in a tag name.
+    #[error("invalid patch id `{0}`")]
+    InvalidPatchId(String),
+
+    /// A patch needs a title.
+    #[error("the title of a patch must not be empty")]
+    EmptyPatchTitle,
+
+    /// There is no tag with the requested name.
+    #[error("tag `{0}` not found")]
+    TagNotFound(String),
+
+    /// No commit could be found for the requested revision.
+    #[error("revision `{0}` not found")]
+    RevisionNotFound(String),
+
+    /// The abbreviated commit id matches more than one commit.
+    #[error("revision `{0}` matches more than one commit")]
+    AmbiguousRevision(String),
+
+    /// The requested revision is neither a commit id, nor a name nor a serialised revision.
+    #[error("invalid revision `{0}`")]
+    InvalidRevision(String),
+
+    /// The cursor of a page of commits is not part of the history that is paged through.
+    #[error("cursor `{0}` is not part of the history")]
+    InvalidCursor(Oid),
+
+    /// There is no syntax highlighting theme with the requested name.
+    #[error("theme `{0}` not found")]
+    ThemeNotFound(String),
+
+    /// The search query is not a valid regular expression.
+    #[error(transparent)]
+    InvalidPattern(#[from] regex::Error),
+
+    /// The blob exceeds the size of blobs that are served raw.
+    #[error("the blob exceeds {0} bytes")]
+    BlobTooLarge(u64),
+
+    /// The blobs of a tree exceed the size of archives that are assembled in memory.
+    #[error("the archive would exceed {0} bytes, export it as a tarball instead")]
+    ArchiveTooLarge(u64),
+
     #[error("missing default branch")]
     MissingDefaultBranch,
 }
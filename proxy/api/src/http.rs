@@ -6,8 +6,13 @@
 
 //! HTTP API delivering JSON over `RESTish` endpoints.
 
+use radicle_source::surf::vcs::git::git2;
 use serde::Deserialize;
-use warp::{filters::BoxedFilter, path, reject, Filter, Rejection, Reply};
+use warp::{
+    filters::BoxedFilter,
+    http::{header, StatusCode},
+    path, reject, reply, Filter, Rejection, Reply,
+};
 
 use link_crypto::{BoxedSigner, PeerId};
 
@@ -242,6 +247,146 @@ pub fn guard_self_revision(
     })
 }
 
+/// The `If-None-Match` header of a conditional `GET`, see [`with_if_none_match`].
+#[derive(Debug, Clone, Default)]
+pub struct IfNoneMatch(Option<String>);
+
+/// Extract the `If-None-Match` header of the request, to answer with [`IfNoneMatch::reply`].
+#[must_use]
+pub fn with_if_none_match() -> BoxedFilter<(IfNoneMatch,)> {
+    warp::header::optional::<String>(header::IF_NONE_MATCH.as_str())
+        .map(IfNoneMatch)
+        .boxed()
+}
+
+impl IfNoneMatch {
+    /// Returns `true` if the client already has the response tagged with `etag`.
+    fn matches(&self, etag: &ETag) -> bool {
+        self.0.as_deref().map_or(false, |tags| {
+            tags.split(',').map(str::trim).any(|tag| {
+                // `If-None-Match` uses the weak comparison.
+                tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag.value
+            })
+        })
+    }
+
+    /// Answer with `304 Not Modified` if the client already has the response tagged with `etag`.
+    /// Otherwise build the response with `reply`. Both carry the `ETag` and `Cache-Control`
+    /// headers.
+    ///
+    /// # Errors
+    ///   * If `reply` fails.
+    pub fn reply<R, F>(&self, etag: &ETag, reply: F) -> Result<reply::Response, Rejection>
+    where
+        R: Reply,
+        F: FnOnce() -> Result<R, Rejection>,
+    {
+        let response = if self.matches(etag) {
+            reply::with_status(reply::reply(), StatusCode::NOT_MODIFIED).into_response()
+        } else {
+            reply()?.into_response()
+        };
+
+        Ok(etag.tag(response))
+    }
+
+    /// Like [`IfNoneMatch::reply`], for responses that need to await other futures to be built.
+    ///
+    /// # Errors
+    ///   * If `reply` fails.
+    pub async fn reply_async<R, F, Fut>(
+        &self,
+        etag: &ETag,
+        reply: F,
+    ) -> Result<reply::Response, Rejection>
+    where
+        R: Reply,
+        F: FnOnce() -> Fut + Send,
+        Fut: std::future::Future<Output = Result<R, Rejection>> + Send,
+    {
+        let response = if self.matches(etag) {
+            reply::with_status(reply::reply(), StatusCode::NOT_MODIFIED).into_response()
+        } else {
+            reply().await?.into_response()
+        };
+
+        Ok(etag.tag(response))
+    }
+}
+
+/// Strong entity tag of a response computed from a commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ETag {
+    /// The quoted tag.
+    value: String,
+    /// `true` if the request named the commit itself rather than a reference to it, which means
+    /// the response can never change.
+    immutable: bool,
+}
+
+impl ETag {
+    /// Tag the response to a request for `revision`, which resolved to the commit `oid`. `parts`
+    /// are the other request parameters that select what is computed from the commit, e.g. a
+    /// path.
+    #[must_use]
+    pub fn new(
+        oid: git2::Oid,
+        revision: Option<&radicle_source::Revision<PeerId>>,
+        parts: &[&str],
+    ) -> Self {
+        let mut key = oid.to_string();
+        for part in parts {
+            key.push('\0');
+            key.push_str(part);
+        }
+        let hash = git2::Oid::hash_object(git2::ObjectType::Blob, key.as_bytes())
+            .expect("hashing a buffer can't fail");
+
+        Self {
+            value: format!("\"{}\"", hash),
+            immutable: matches!(revision, Some(radicle_source::Revision::Sha { .. })),
+        }
+    }
+
+    /// Tag the response computed from `value` rather than from a commit. For responses that are
+    /// cheaper to compute than to send, like lists of references.
+    #[must_use]
+    pub fn of<T: serde::Serialize>(value: &T) -> Self {
+        Self::new(
+            git2::Oid::zero(),
+            None,
+            &[&serde_json::to_string(value).unwrap_or_default()],
+        )
+    }
+
+    /// Have clients revalidate the response even if the commit was requested by id, because it
+    /// also depends on local state.
+    #[must_use]
+    pub const fn mutable(mut self) -> Self {
+        self.immutable = false;
+        self
+    }
+
+    /// Add the `ETag` and `Cache-Control` headers for the tag to `response`.
+    fn tag(&self, mut response: reply::Response) -> reply::Response {
+        let headers = response.headers_mut();
+        headers.insert(
+            header::ETAG,
+            header::HeaderValue::from_str(&self.value).expect("entity tags are hex strings"),
+        );
+        headers.insert(
+            header::CACHE_CONTROL,
+            header::HeaderValue::from_static(if self.immutable {
+                "private, max-age=31536000, immutable"
+            } else {
+                "private, no-cache"
+            }),
+        );
+
+        response
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -349,4 +494,50 @@ mod test {
             );
         });
     }
+
+    fn if_none_match_test_filter() -> BoxedFilter<(impl Reply,)> {
+        with_if_none_match()
+            .and_then(|if_none_match: IfNoneMatch| async move {
+                let etag = ETag::new(git2::Oid::zero(), None, &["path"]);
+                if_none_match.reply(&etag, || Ok(warp::reply::json(&72)))
+            })
+            .boxed()
+    }
+
+    #[tokio::test]
+    async fn if_none_match() {
+        let api = if_none_match_test_filter();
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/")
+            .reply(&api)
+            .await;
+        assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, serde_json::json!(72));
+        });
+        assert_eq!(res.headers()[header::CACHE_CONTROL], "private, no-cache");
+        let etag = res.headers()[header::ETAG].clone();
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/")
+            .header(
+                header::IF_NONE_MATCH,
+                format!("\"other\", W/{}", etag.to_str().unwrap()),
+            )
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(res.body().is_empty());
+        assert_eq!(res.headers()[header::ETAG], etag);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/")
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
 }
@@ -57,7 +57,7 @@ mod handler {
 
     use warp::{http::StatusCode, reply, Rejection, Reply};
 
-    use crate::{browser, context, error, project};
+    use crate::{context, error, project};
 
     /// Create a project from the fixture repo.
     #[allow(clippy::let_underscore_must_use)]
@@ -79,8 +79,7 @@ mod handler {
         let branch = radicle_daemon::state::get_branch(&ctx.peer, meta.urn(), None, None)
             .await
             .map_err(error::Error::from)?;
-        let stats = browser::using(&ctx.peer, branch, |browser| Ok(browser.get_stats()?))
-            .map_err(error::Error::from)?;
+        let stats = project::stats(&ctx.peer, branch)?;
         let project = project::Full::try_from((meta, stats))?;
 
         Ok(reply::with_status(
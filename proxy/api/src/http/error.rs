@@ -223,6 +223,11 @@ pub async fn recover(err: Rejection) -> Result<impl Reply, Infallible> {
                     ),
                 },
                 error::Error::Source(err) => recover_source(err),
+                error::Error::Git(git_error) => (
+                    StatusCode::BAD_REQUEST,
+                    "GIT_ERROR",
+                    format!("Internal Git error: {}", git_error),
+                ),
                 error::Error::Keystore(keystore_err) => {
                     if keystore_err.is_invalid_passphrase() {
                         (
@@ -247,6 +252,53 @@ pub async fn recover(err: Rejection) -> Result<impl Reply, Infallible> {
                 error::Error::KeystoreSealed | error::Error::InvalidAuthCookie => {
                     (StatusCode::FORBIDDEN, "FORBIDDEN", err.to_string())
                 },
+                error::Error::PathNotFound(_)
+                | error::Error::TagNotFound(_)
+                | error::Error::RevisionNotFound(_)
+                | error::Error::CommitNotFound(_)
+                | error::Error::PatchNotFound(_)
+                | error::Error::PatchRevisionNotFound { .. }
+                | error::Error::CommentNotFound(_) => {
+                    (StatusCode::NOT_FOUND, "NOT_FOUND", err.to_string())
+                },
+                error::Error::InvalidRevision(_) => {
+                    (StatusCode::BAD_REQUEST, "INVALID_REVISION", err.to_string())
+                },
+                error::Error::AmbiguousRevision(_) => {
+                    (StatusCode::BAD_REQUEST, "AMBIGUOUS_REVISION", err.to_string())
+                },
+                error::Error::InvalidCursor(_) => {
+                    (StatusCode::BAD_REQUEST, "INVALID_CURSOR", err.to_string())
+                },
+                error::Error::PatchExists(_) => {
+                    (StatusCode::CONFLICT, "PATCH_EXISTS", err.to_string())
+                },
+                error::Error::InvalidPatchId(_) => {
+                    (StatusCode::BAD_REQUEST, "INVALID_PATCH_ID", err.to_string())
+                },
+                error::Error::EmptyPatchTitle => {
+                    (StatusCode::BAD_REQUEST, "EMPTY_PATCH_TITLE", err.to_string())
+                },
+                error::Error::EmptyComment => {
+                    (StatusCode::BAD_REQUEST, "EMPTY_COMMENT", err.to_string())
+                },
+                error::Error::ThemeNotFound(_) => {
+                    (StatusCode::BAD_REQUEST, "UNKNOWN_THEME", err.to_string())
+                },
+                error::Error::InvalidPattern(_) => {
+                    (StatusCode::BAD_REQUEST, "INVALID_PATTERN", err.to_string())
+                },
+                error::Error::InvalidPath(_) => {
+                    (StatusCode::BAD_REQUEST, "INVALID_PATH", err.to_string())
+                },
+                error::Error::BlobTooLarge(_) => (
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "BLOB_TOO_LARGE",
+                    err.to_string(),
+                ),
+                error::Error::ArchiveTooLarge(_) => {
+                    (StatusCode::BAD_REQUEST, "ARCHIVE_TOO_LARGE", err.to_string())
+                },
                 error::Error::SessionInUse(_) => {
                     (StatusCode::BAD_REQUEST, "SESSION_IN_USE", err.to_string())
                 },
@@ -14,13 +14,15 @@ use warp::{filters::BoxedFilter, path, Filter, Rejection, Reply};
 use link_crypto::PeerId;
 use link_identities::git::Urn;
 
-use crate::{context, http};
+use crate::{context, http, patch};
 
 mod request;
 
 /// Combination of all routes.
 pub fn filters(ctx: context::Context) -> BoxedFilter<(impl Reply,)> {
-    checkout_filter(ctx.clone())
+    activity_filter(ctx.clone())
+        .or(checkout_filter(ctx.clone()))
+        .or(comments_filter(ctx.clone()))
         .or(create_filter(ctx.clone()))
         .or(failed_filter(ctx.clone()))
         .or(get_filter(ctx.clone()))
@@ -29,12 +31,31 @@ pub fn filters(ctx: context::Context) -> BoxedFilter<(impl Reply,)> {
         .or(peers_filter(ctx.clone()))
         .or(path("requests").and(request::filters(ctx.clone())))
         .or(track_filter(ctx.clone()))
+        .or(create_patch_filter(ctx.clone()))
+        .or(close_patch_filter(ctx.clone()))
+        .or(patch_filter(ctx.clone()))
         .or(patches_filter(ctx.clone()))
+        .or(post_comment_filter(ctx.clone()))
+        .or(range_diff_filter(ctx.clone()))
+        .or(update_patch_filter(ctx.clone()))
         .or(untrack_filter(ctx.clone()))
         .or(user_filter(ctx))
         .boxed()
 }
 
+/// `GET /<urn>/activity?revision=<revision>`
+fn activity_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("activity"))
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs_opt::<ActivityQuery>())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::activity)
+}
+
 /// `POST /<urn>/checkout`
 fn checkout_filter(
     ctx: context::Context,
@@ -48,6 +69,21 @@ fn checkout_filter(
         .and_then(handler::checkout)
 }
 
+/// `GET /<urn>/comments/<peer_id>/<id>`
+///
+/// Get the discussion of a patch.
+fn comments_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("comments"))
+        .and(path::param::<PeerId>())
+        .and(path::tail())
+        .and(warp::get())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::comments)
+}
+
 /// `POST /`
 fn create_filter(
     ctx: context::Context,
@@ -154,9 +190,53 @@ fn user_filter(
         .and_then(handler::list_user)
 }
 
-/// `GET /<urn>/patches`
+/// `POST /<urn>/patches`
+///
+/// Create a patch from a revision in the namespace of the local peer.
+fn create_patch_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("patches"))
+        .and(path::end())
+        .and(warp::post())
+        .and(http::with_context_unsealed(ctx))
+        .and(warp::body::json())
+        .and_then(handler::create_patch)
+}
+
+/// `DELETE /<urn>/patches/<id>`
+///
+/// Close a patch of the local peer.
+fn close_patch_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("patches"))
+        .and(path::tail())
+        .and(warp::delete())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::close_patch)
+}
+
+/// `GET /<urn>/patches/<peer_id>/<id>`
 ///
-/// Get the list of patches for the project.
+/// Get a patch with the commits and changes it introduces.
+fn patch_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("patches"))
+        .and(path::param::<PeerId>())
+        .and(path::tail())
+        .and(warp::get())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::patch)
+}
+
+/// `GET /<urn>/patches?status=<status>`
+///
+/// Get the list of patches for the project, optionally only those with the given status.
 fn patches_filter(
     ctx: context::Context,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
@@ -164,21 +244,93 @@ fn patches_filter(
         .and(path("patches"))
         .and(path::end())
         .and(warp::get())
+        .and(http::with_qs_opt::<PatchesQuery>())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::patches)
 }
 
+/// `POST /<urn>/comments/<peer_id>/<id>`
+///
+/// Comment on a patch.
+fn post_comment_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("comments"))
+        .and(path::param::<PeerId>())
+        .and(path::tail())
+        .and(warp::post())
+        .and(http::with_context_unsealed(ctx))
+        .and(warp::body::json())
+        .and_then(handler::post_comment)
+}
+
+/// `GET /<urn>/range-diff/<peer_id>/<id>?from=<number>&to=<number>`
+///
+/// Compare two revisions of a patch.
+fn range_diff_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("range-diff"))
+        .and(path::param::<PeerId>())
+        .and(path::tail())
+        .and(warp::get())
+        .and(http::with_qs_opt::<RangeDiffQuery>())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::range_diff)
+}
+
+/// `PUT /<urn>/patches/<id>`
+///
+/// Publish a new revision of a patch of the local peer.
+fn update_patch_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path::param::<Urn>()
+        .and(path("patches"))
+        .and(path::tail())
+        .and(warp::put())
+        .and(http::with_context_unsealed(ctx))
+        .and(warp::body::json())
+        .and_then(handler::update_patch)
+}
+
 /// Project handlers to implement conversion and translation between core domain and http request
 /// fullfilment.
 mod handler {
     use std::convert::TryFrom;
 
-    use warp::{http::StatusCode, reply, Rejection, Reply};
+    use warp::{http::StatusCode, path::Tail, reply, Rejection, Reply};
 
     use link_crypto::PeerId;
     use link_identities::git::Urn;
 
-    use crate::{browser, context, error::Error, http, patch, project};
+    use crate::{context, error::Error, http, patch, project, source};
+
+    /// Count the commits per author and week, see [`source::activity::activity`].
+    pub async fn activity(
+        urn: Urn,
+        query: Option<super::ActivityQuery>,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let super::ActivityQuery { revision } = query.unwrap_or_default();
+        let revision = http::guard_self_revision(&ctx.peer, revision);
+
+        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn.clone())
+            .await
+            .map_err(Error::from)?;
+        let peers = radicle_daemon::state::list_project_peers(&ctx.peer, urn.clone())
+            .await
+            .map_err(Error::from)?
+            .into_iter()
+            .map(project::Peer::from)
+            .collect::<Vec<_>>();
+        let activity =
+            source::activity::activity(&ctx.peer, default_branch, revision, &urn, peers)?;
+
+        Ok(reply::json(&activity))
+    }
 
     /// Checkout a [`project::Project`]'s source code.
     pub async fn checkout(
@@ -193,6 +345,19 @@ mod handler {
         Ok(reply::with_status(reply::json(&path), StatusCode::CREATED))
     }
 
+    /// List the discussion of a patch, see [`patch::comment::list`].
+    pub async fn comments(
+        project_urn: Urn,
+        peer_id: PeerId,
+        id: Tail,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        let comments = patch::comment::list(&ctx.peer, project_urn, peer_id, &id).await?;
+
+        Ok(reply::json(&comments))
+    }
+
     /// Create a new [`project::Project`].
     pub async fn create(
         ctx: context::Unsealed,
@@ -212,10 +377,7 @@ mod handler {
         )
         .await
         .map_err(Error::from)?;
-        let stats = browser::using(&ctx.peer, branch, |browser| {
-            browser.get_stats().map_err(radicle_source::Error::from)
-        })
-        .map_err(Error::from)?;
+        let stats = project::stats(&ctx.peer, branch)?;
         let project = project::Full::try_from((project, stats))?;
 
         Ok(reply::with_status(
@@ -224,6 +386,62 @@ mod handler {
         ))
     }
 
+    /// Create a patch of the local peer, see [`patch::create`].
+    pub async fn create_patch(
+        project_urn: Urn,
+        ctx: context::Unsealed,
+        super::CreatePatchInput {
+            revision,
+            id,
+            title,
+            description,
+        }: super::CreatePatchInput,
+    ) -> Result<impl Reply, Rejection> {
+        let revision = local_revision(&ctx.peer, revision)?;
+        let patch =
+            patch::create(&ctx.peer, project_urn, revision, id, &title, &description).await?;
+
+        Ok(reply::with_status(reply::json(&patch), StatusCode::CREATED))
+    }
+
+    /// Close a patch of the local peer, see [`patch::close`].
+    pub async fn close_patch(
+        project_urn: Urn,
+        id: Tail,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        patch::close(&ctx.peer, project_urn, &id).await?;
+
+        Ok(reply::with_status(reply::reply(), StatusCode::NO_CONTENT))
+    }
+
+    /// Publish a new revision of a patch of the local peer, see [`patch::update`].
+    pub async fn update_patch(
+        project_urn: Urn,
+        id: Tail,
+        ctx: context::Unsealed,
+        super::UpdatePatchInput {
+            revision,
+            title,
+            description,
+        }: super::UpdatePatchInput,
+    ) -> Result<impl Reply, Rejection> {
+        let revision = local_revision(&ctx.peer, revision)?;
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        let patch = patch::update(
+            &ctx.peer,
+            project_urn,
+            &id,
+            revision,
+            title.as_deref(),
+            description.as_deref(),
+        )
+        .await?;
+
+        Ok(reply::json(&patch))
+    }
+
     /// Get the [`project::Project`] for the given `id`.
     pub async fn get(urn: Urn, ctx: context::Unsealed) -> Result<impl Reply, Rejection> {
         Ok(reply::json(&project::get(&ctx.peer, urn).await?))
@@ -296,17 +514,145 @@ mod handler {
         Ok(reply::json(&true))
     }
 
+    /// Get a single patch of a project, see [`patch::get`].
+    pub async fn patch(
+        project_urn: Urn,
+        peer_id: PeerId,
+        id: Tail,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        let patch = patch::get(&ctx.peer, project_urn, peer_id, &id).await?;
+
+        Ok(reply::json(&patch))
+    }
+
+    /// Comment on a patch, see [`patch::comment::post`].
+    pub async fn post_comment(
+        project_urn: Urn,
+        peer_id: PeerId,
+        id: Tail,
+        ctx: context::Unsealed,
+        super::CommentInput { body, reply_to }: super::CommentInput,
+    ) -> Result<impl Reply, Rejection> {
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        let comment =
+            patch::comment::post(&ctx.peer, project_urn, peer_id, &id, &body, reply_to).await?;
+
+        Ok(reply::with_status(
+            reply::json(&comment),
+            StatusCode::CREATED,
+        ))
+    }
+
+    /// Compare two revisions of a patch, see [`patch::range_diff`].
+    pub async fn range_diff(
+        project_urn: Urn,
+        peer_id: PeerId,
+        id: Tail,
+        query: Option<super::RangeDiffQuery>,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let super::RangeDiffQuery { from, to } = query.unwrap_or_default();
+        let id = percent_encoding::percent_decode_str(id.as_str()).decode_utf8_lossy();
+        let range_diff = patch::range_diff(&ctx.peer, project_urn, peer_id, &id, from, to).await?;
+
+        Ok(reply::json(&range_diff))
+    }
+
     /// Get the list of patches for a project
     pub async fn patches(
         project_urn: Urn,
+        query: Option<super::PatchesQuery>,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
+        let super::PatchesQuery { status } = query.unwrap_or_default();
         let patches = patch::list(&ctx.peer, project_urn)
             .await
-            .map_err(Error::from)?;
+            .map_err(Error::from)?
+            .into_iter()
+            .filter(|patch| status.map_or(true, |status| patch.status == status))
+            .collect::<Vec<_>>();
 
         Ok(reply::json(&patches))
     }
+
+    /// Patches can only be published for revisions in the namespace of the local peer.
+    fn local_revision(
+        peer: &radicle_daemon::net::peer::Peer<link_crypto::BoxedSigner>,
+        revision: Option<radicle_source::Revision<PeerId>>,
+    ) -> Result<Option<radicle_source::Revision<PeerId>>, Error> {
+        match http::guard_self_revision(peer, revision) {
+            Some(radicle_source::Revision::Branch {
+                name,
+                peer_id: Some(peer_id),
+            }) => Err(Error::InvalidRevision(format!("{}/{}", peer_id, name))),
+            revision => Ok(revision),
+        }
+    }
+}
+
+/// Query of the activity endpoint.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityQuery {
+    /// Revision to walk the history of, the default branch if `None`.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled input data for patch creation.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePatchInput {
+    /// Branch or commit of the local peer to propose, the default branch if `None`.
+    revision: Option<radicle_source::Revision<PeerId>>,
+    /// Id of the patch, derived from the title if `None`.
+    id: Option<String>,
+    /// Summary of the patch, the first line of its message.
+    title: String,
+    /// Long form outline of the patch, the rest of its message.
+    #[serde(default)]
+    description: String,
+}
+
+/// Query of the patch list endpoint.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchesQuery {
+    /// Only list the patches with this status, all of them if `None`.
+    status: Option<patch::Status>,
+}
+
+/// Bundled input data for a new revision of a patch.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePatchInput {
+    /// Branch or commit of the local peer to propose, the current commit is kept if `None`.
+    revision: Option<radicle_source::Revision<PeerId>>,
+    /// New summary of the patch, the current one is kept if `None`.
+    title: Option<String>,
+    /// New outline of the patch, the current one is kept if `None`.
+    description: Option<String>,
+}
+
+/// Query of the range diff endpoint.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeDiffQuery {
+    /// Number of the older revision, the second to last one if `None`.
+    from: Option<usize>,
+    /// Number of the newer revision, the last one if `None`.
+    to: Option<usize>,
+}
+
+/// Bundled input data for a comment on a patch.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentInput {
+    /// Text of the comment.
+    body: String,
+    /// The comment to reply to, `None` to start a new thread.
+    reply_to: Option<radicle_git_ext::Oid>,
 }
 
 /// Bundled input data for project creation.
@@ -354,6 +700,61 @@ mod test {
 
     use crate::{context, http, identity, project, session};
 
+    #[tokio::test]
+    async fn activity() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = {
+            let owner = radicle_daemon::state::init_owner(
+                &ctx.peer,
+                Person {
+                    name: "cloudhead".into(),
+                },
+            )
+            .await?;
+            let platinum_project = crate::control::replicate_platinum(
+                &ctx.peer,
+                &owner,
+                "git-platinum",
+                "fixture data",
+                crate::control::default_branch(),
+            )
+            .await?;
+            platinum_project.urn()
+        };
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/{}/activity", urn))
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            let authors = have["authors"].as_array().unwrap();
+            assert!(!authors.is_empty());
+
+            let mut total = 0;
+            for author in authors {
+                let commits = author["commits"].as_u64().unwrap();
+                let weekly = author["weeks"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|week| week["commits"].as_u64().unwrap())
+                    .sum::<u64>();
+                assert_eq!(commits, weekly);
+                // The local peer is the only one with branches, so it has all commits.
+                assert_eq!(author["peer"]["peerId"], json!(ctx.peer.peer_id()));
+                total += commits;
+            }
+            assert_eq!(total, 15);
+        });
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn checkout() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
@@ -499,6 +900,7 @@ mod test {
                 "branches": 1,
                 "commits": 1,
                 "contributors": 1,
+                "languages": [],
             },
         });
 
@@ -564,7 +966,20 @@ mod test {
         let meta = projects.into_iter().next().unwrap();
         let maintainer = meta.metadata.maintainers.iter().next().unwrap();
 
-        let have: Value = serde_json::from_slice(res.body()).unwrap();
+        let mut have: Value = serde_json::from_slice(res.body()).unwrap();
+        let languages = have["stats"]
+            .as_object_mut()
+            .and_then(|stats| stats.remove("languages"))
+            .unwrap();
+        let languages = languages
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|language| language["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(languages.contains(&"Rust"));
+        assert!(languages.contains(&"Haskell"));
+
         let want = json!({
             "urn": meta.urn,
             "metadata": {
@@ -588,6 +1003,424 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn create_patch() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = {
+            let owner = radicle_daemon::state::init_owner(
+                &ctx.peer,
+                Person {
+                    name: "cloudhead".into(),
+                },
+            )
+            .await?;
+            let platinum_project = crate::control::replicate_platinum(
+                &ctx.peer,
+                &owner,
+                "git-platinum",
+                "fixture data",
+                crate::control::default_branch(),
+            )
+            .await?;
+            platinum_project.urn()
+        };
+
+        let input = json!({
+            "revision": { "type": "branch", "name": "dev" },
+            "title": "Add dev changes",
+            "description": "All the work on dev.",
+        });
+        let res = request()
+            .method("POST")
+            .path(&format!("/{}/patches", urn))
+            .json(&input)
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::CREATED, |have| {
+            assert_eq!(have["id"], json!("add-dev-changes"));
+            assert_eq!(have["status"], json!("open"));
+            assert_eq!(have["peer"]["peerId"], json!(ctx.peer.peer_id()));
+            assert_eq!(
+                have["message"],
+                json!("Add dev changes\n\nAll the work on dev.")
+            );
+            assert!(!have["commits"].as_array().unwrap().is_empty());
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/{}/patches/{}/add-dev-changes",
+                urn,
+                ctx.peer.peer_id()
+            ))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["id"], json!("add-dev-changes"));
+        });
+
+        for (status, count) in &[("open", 1), ("merged", 0), ("closed", 0)] {
+            let res = request()
+                .method("GET")
+                .path(&format!("/{}/patches?status={}", urn, status))
+                .reply(&api)
+                .await;
+            http::test::assert_response(&res, StatusCode::OK, |have| {
+                assert_eq!(have.as_array().unwrap().len(), *count);
+            });
+        }
+
+        // The same patch can't be created twice.
+        let res = request()
+            .method("POST")
+            .path(&format!("/{}/patches", urn))
+            .json(&input)
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::CONFLICT, |have| {
+            assert_eq!(have["variant"], json!("PATCH_EXISTS"));
+        });
+
+        let res = request()
+            .method("DELETE")
+            .path(&format!("/{}/patches/add-dev-changes", urn))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        for (status, count) in &[("open", 0), ("closed", 1)] {
+            let res = request()
+                .method("GET")
+                .path(&format!("/{}/patches?status={}", urn, status))
+                .reply(&api)
+                .await;
+            http::test::assert_response(&res, StatusCode::OK, |have| {
+                assert_eq!(have.as_array().unwrap().len(), *count);
+            });
+        }
+
+        // Closing a closed patch leaves it closed.
+        let res = request()
+            .method("DELETE")
+            .path(&format!("/{}/patches/add-dev-changes", urn))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/{}/patches/{}/add-dev-changes",
+                urn,
+                ctx.peer.peer_id()
+            ))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["status"], json!("closed"));
+        });
+
+        let res = request()
+            .method("DELETE")
+            .path(&format!("/{}/patches/missing", urn))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::NOT_FOUND, |_| {});
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_patch() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = {
+            let owner = radicle_daemon::state::init_owner(
+                &ctx.peer,
+                Person {
+                    name: "cloudhead".into(),
+                },
+            )
+            .await?;
+            let platinum_project = crate::control::replicate_platinum(
+                &ctx.peer,
+                &owner,
+                "git-platinum",
+                "fixture data",
+                crate::control::default_branch(),
+            )
+            .await?;
+            platinum_project.urn()
+        };
+
+        let res = request()
+            .method("POST")
+            .path(&format!("/{}/patches", urn))
+            .json(&json!({
+                "revision": { "type": "branch", "name": "dev" },
+                "id": "dev",
+                "title": "Add dev changes",
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/{}/patches/{}/dev", urn, ctx.peer.peer_id()))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["id"], json!("dev"));
+            assert_eq!(have["status"], json!("open"));
+            assert_eq!(have["message"], json!("Add dev changes"));
+            assert_eq!(have["peer"]["peerId"], json!(ctx.peer.peer_id()));
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/{}/patches/{}/missing", urn, ctx.peer.peer_id()))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::NOT_FOUND, |have| {
+            assert_eq!(have["variant"], json!("NOT_FOUND"));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_patch() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = {
+            let owner = radicle_daemon::state::init_owner(
+                &ctx.peer,
+                Person {
+                    name: "cloudhead".into(),
+                },
+            )
+            .await?;
+            let platinum_project = crate::control::replicate_platinum(
+                &ctx.peer,
+                &owner,
+                "git-platinum",
+                "fixture data",
+                crate::control::default_branch(),
+            )
+            .await?;
+            platinum_project.urn()
+        };
+
+        let res = request()
+            .method("POST")
+            .path(&format!("/{}/patches", urn))
+            .json(&json!({
+                "revision": { "type": "branch", "name": "dev" },
+                "id": "dev",
+                "title": "Add dev changes",
+            }))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::CREATED, |have| {
+            assert_eq!(have["revisions"].as_array().unwrap().len(), 1);
+        });
+
+        // A single revision has nothing to compare with.
+        let res = request()
+            .method("GET")
+            .path(&format!("/{}/range-diff/{}/dev", urn, ctx.peer.peer_id()))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["from"]["number"], json!(1));
+            assert_eq!(have["to"]["number"], json!(1));
+            assert_eq!(have["commits"], json!([]));
+        });
+
+        let res = request()
+            .method("PUT")
+            .path(&format!("/{}/patches/dev", urn))
+            .json(&json!({
+                "revision": { "type": "branch", "name": "dev" },
+                "description": "All the work on dev.",
+            }))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have["message"],
+                json!("Add dev changes\n\nAll the work on dev.")
+            );
+            let revisions = have["revisions"].as_array().unwrap();
+            assert_eq!(revisions.len(), 2);
+            assert_eq!(revisions[0]["number"], json!(1));
+            assert_eq!(revisions[0]["message"], json!("Add dev changes"));
+            assert_eq!(revisions[1]["number"], json!(2));
+            assert_eq!(revisions[1]["commit"], have["commit"]);
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/{}/range-diff/{}/dev?from=1&to=2",
+                urn,
+                ctx.peer.peer_id()
+            ))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["from"]["number"], json!(1));
+            assert_eq!(have["to"]["number"], json!(2));
+            // Both revisions point to the same commit.
+            let commits = have["commits"].as_array().unwrap();
+            assert!(!commits.is_empty());
+            for pair in commits {
+                assert_eq!(pair["status"], json!("unchanged"));
+                assert_eq!(pair["old"], pair["new"]);
+            }
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/{}/range-diff/{}/dev?to=3",
+                urn,
+                ctx.peer.peer_id()
+            ))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::NOT_FOUND, |_| {});
+
+        // Without a revision the commit of the patch is kept.
+        let res = request()
+            .method("GET")
+            .path(&format!("/{}/patches/{}/dev", urn, ctx.peer.peer_id()))
+            .reply(&api)
+            .await;
+        let patch: Value = serde_json::from_slice(res.body())?;
+        let res = request()
+            .method("PUT")
+            .path(&format!("/{}/patches/dev", urn))
+            .json(&json!({ "description": "Only the work on dev." }))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["commit"], patch["commit"]);
+            assert_eq!(
+                have["message"],
+                json!("Add dev changes\n\nOnly the work on dev.")
+            );
+            assert_eq!(have["revisions"].as_array().unwrap().len(), 3);
+        });
+
+        // Nothing changed, so no revision is recorded.
+        let res = request()
+            .method("PUT")
+            .path(&format!("/{}/patches/dev", urn))
+            .json(&json!({ "title": "Add dev changes" }))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["commit"], patch["commit"]);
+            assert_eq!(have["revisions"].as_array().unwrap().len(), 3);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn comments() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = {
+            let owner = radicle_daemon::state::init_owner(
+                &ctx.peer,
+                Person {
+                    name: "cloudhead".into(),
+                },
+            )
+            .await?;
+            let platinum_project = crate::control::replicate_platinum(
+                &ctx.peer,
+                &owner,
+                "git-platinum",
+                "fixture data",
+                crate::control::default_branch(),
+            )
+            .await?;
+            platinum_project.urn()
+        };
+
+        let res = request()
+            .method("POST")
+            .path(&format!("/{}/patches", urn))
+            .json(&json!({
+                "revision": { "type": "branch", "name": "dev" },
+                "id": "dev",
+                "title": "Add dev changes",
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let path = format!("/{}/comments/{}/dev", urn, ctx.peer.peer_id());
+        let res = request()
+            .method("POST")
+            .path(&path)
+            .json(&json!({ "body": "Looks good." }))
+            .reply(&api)
+            .await;
+        let comment: Value = serde_json::from_slice(res.body())?;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(comment["peerId"], json!(ctx.peer.peer_id()));
+        assert_eq!(comment["replyTo"], Value::Null);
+
+        let res = request()
+            .method("POST")
+            .path(&path)
+            .json(&json!({ "body": "Thanks!", "replyTo": comment["id"] }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            let threads = have.as_array().unwrap();
+            assert_eq!(threads.len(), 1);
+            assert_eq!(threads[0]["body"], json!("Looks good."));
+            assert_eq!(threads[0]["peerId"], json!(ctx.peer.peer_id()));
+            let replies = threads[0]["replies"].as_array().unwrap();
+            assert_eq!(replies.len(), 1);
+            assert_eq!(replies[0]["body"], json!("Thanks!"));
+            assert_eq!(replies[0]["replyTo"], comment["id"]);
+        });
+
+        // Replies need an existing comment.
+        let res = request()
+            .method("POST")
+            .path(&path)
+            .json(&json!({
+                "body": "Lost",
+                "replyTo": "0000000000000000000000000000000000000001",
+            }))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::NOT_FOUND, |_| {});
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn get() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
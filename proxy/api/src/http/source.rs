@@ -6,6 +6,9 @@
 
 //! Endpoints and serialisation for source code browsing.
 
+use std::io;
+
+use futures::{channel::mpsc, executor, SinkExt as _};
 use serde::{Deserialize, Serialize};
 use warp::{filters::BoxedFilter, path, Filter, Rejection, Reply};
 
@@ -13,20 +16,60 @@ use link_crypto::PeerId;
 use link_identities::git::Urn;
 use radicle_git_ext::Oid;
 
-use crate::{context, http};
+use crate::{context, error, http, source};
 
 /// Combination of all source filters.
 pub fn filters(ctx: context::Context) -> BoxedFilter<(impl Reply,)> {
-    blob_filter(ctx.clone())
+    archive_filter(ctx.clone())
+        .or(blame_filter(ctx.clone()))
+        .or(blob_filter(ctx.clone()))
         .or(branches_filter(ctx.clone()))
         .or(commit_filter(ctx.clone()))
         .or(commits_filter(ctx.clone()))
-        .or(local_state_filter())
+        .or(compare_filter(ctx.clone()))
+        .or(graph_filter(ctx.clone()))
+        .or(history_filter(ctx.clone()))
+        .or(local_state_filter(ctx.clone()))
+        .or(raw_filter(ctx.clone()))
+        .or(readme_filter(ctx.clone()))
+        .or(resolve_filter(ctx.clone()))
+        .or(search_filter(ctx.clone()))
+        .or(tag_filter(ctx.clone()))
         .or(tags_filter(ctx.clone()))
+        .or(themes_filter(ctx.clone()))
         .or(tree_filter(ctx))
         .boxed()
 }
 
+/// `GET /archive/<project_urn>?format=<format>&prefix=<prefix>&peerId=<peer_id>&
+/// revision=<revision>`
+fn archive_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("archive")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs_opt::<ArchiveQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::archive)
+}
+
+/// `GET /blame/<project_urn>?path=<path>&peerId=<peer_id>&revision=<revision>`
+fn blame_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("blame")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<BlameQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::blame)
+}
+
 /// `GET /blob/<project_urn>?revision=<revision>&path=<path>`
 fn blob_filter(
     ctx: context::Context,
@@ -35,6 +78,7 @@ fn blob_filter(
         .and(path::param::<Urn>())
         .and(path::end())
         .and(http::with_qs::<BlobQuery>())
+        .and(http::with_if_none_match())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::blob)
 }
@@ -48,19 +92,24 @@ fn branches_filter(
         .and(path::end())
         .and(warp::query::<BranchQuery>())
         .and(warp::get())
+        .and(http::with_if_none_match())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::branches)
 }
 
-/// `GET /commit/<project_urn>/<sha1>`
+/// `GET /commit/<project_urn>/<sha1>?peerId=<peer_id>`
+///
+/// `sha1` may be abbreviated.
 fn commit_filter(
     ctx: context::Context,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     path("commit")
         .and(path::param::<Urn>())
-        .and(path::param::<Oid>())
+        .and(path::param::<String>())
         .and(path::end())
         .and(warp::get())
+        .and(http::with_qs_opt::<CommitQuery>())
+        .and(http::with_if_none_match())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::commit)
 }
@@ -74,18 +123,137 @@ fn commits_filter(
         .and(http::with_context_unsealed(ctx))
         .and(path::param::<Urn>())
         .and(http::with_qs::<CommitsQuery>())
+        .and(http::with_if_none_match())
         .and_then(handler::commits)
 }
 
+/// `GET /compare/<project_urn>?base=<revision>&head=<revision>`
+fn compare_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("compare")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<CompareQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::compare)
+}
+
+/// `GET /graph/<project_urn>?peerIds=<peer_ids>&limit=<limit>`
+fn graph_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("graph")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs_opt::<GraphQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::graph)
+}
+
+/// `GET /history/<project_urn>?path=<path>&peerId=<peer_id>&revision=<revision>`
+fn history_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("history")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<HistoryQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::history)
+}
+
 /// `GET /local-state?path=<path>`
-fn local_state_filter() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+fn local_state_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     path("local-state")
         .and(warp::get())
         .and(http::with_qs::<LocalStateQuery>())
+        .and(http::with_context_unsealed(ctx))
         .and_then(handler::local_state)
 }
 
-/// `GET /tags/<project_urn>?peer_id=<peer_id>`
+/// `GET /raw/<project_urn>?path=<path>&peerId=<peer_id>&revision=<revision>`
+///
+/// Blobs larger than [`source::raw::MAX_SIZE`] are refused with `413 Payload Too Large`.
+fn raw_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("raw")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<RawQuery>())
+        .and(warp::header::optional::<String>("range"))
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::raw)
+}
+
+/// `GET /readme/<project_urn>?prefix=<prefix>&peerId=<peer_id>&revision=<revision>`
+fn readme_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("readme")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs_opt::<ReadmeQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::readme)
+}
+
+/// `GET /resolve/<project_urn>?rev=<revision>`
+fn resolve_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("resolve")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<ResolveQuery>())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::resolve)
+}
+
+/// `GET /search/<project_urn>?q=<pattern>&regex=<bool>&caseSensitive=<bool>&path=<path>&
+/// limit=<limit>&peerId=<peer_id>&revision=<revision>`
+fn search_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("search")
+        .and(path::param::<Urn>())
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_qs::<SearchQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::search)
+}
+
+/// `GET /tag/<project_urn>/<name>?peerId=<peer_id>`
+fn tag_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("tag")
+        .and(path::param::<Urn>())
+        .and(path::tail())
+        .and(warp::get())
+        .and(warp::query::<TagQuery>())
+        .and(http::with_if_none_match())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::tag)
+}
+
+/// `GET /tags/<project_urn>?peerId=<peer_id>`
 fn tags_filter(
     ctx: context::Context,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
@@ -94,10 +262,22 @@ fn tags_filter(
         .and(warp::query::<TagQuery>())
         .and(path::end())
         .and(warp::get())
+        .and(http::with_if_none_match())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::tags)
 }
 
+/// `GET /themes`
+fn themes_filter(
+    ctx: context::Context,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    path("themes")
+        .and(path::end())
+        .and(warp::get())
+        .and(http::with_context_unsealed(ctx))
+        .and_then(handler::themes)
+}
+
 /// `GET /tree/<project_urn>?peerId=<peer_id>&prefix=<prefix>*revision=<revision>`
 fn tree_filter(
     ctx: context::Context,
@@ -107,21 +287,140 @@ fn tree_filter(
         .and(path::end())
         .and(warp::get())
         .and(http::with_qs::<TreeQuery>())
+        .and(http::with_if_none_match())
         .and(http::with_context_unsealed(ctx))
         .and_then(handler::tree)
 }
 
 /// Source handlers for conversion between core domain and http request fullfilment.
 mod handler {
-    use warp::{reply, Rejection, Reply};
-
+    use std::{path::Path, sync::Arc};
+
+    use futures::channel::mpsc;
+    use warp::{
+        http::{header, Response, StatusCode},
+        hyper::Body,
+        path::Tail,
+        reply, Rejection, Reply,
+    };
+
+    use librad::git::types::{Reference, Single};
+    use link_crypto::PeerId;
     use link_identities::git::Urn;
-    use radicle_git_ext::Oid;
-    use radicle_source::surf::vcs::git::RefScope;
+    use radicle_source::surf::vcs::git::{git2, RefScope};
+
+    use crate::{browser, context, error, http::IfNoneMatch, source};
+
+    /// Resolve `revision` in the namespace of `branch` to the commit it points to, which the
+    /// entity tags of responses are derived from.
+    fn resolve_revision(
+        ctx: &context::Unsealed,
+        branch: Reference<Single>,
+        revision: Option<radicle_source::Revision<PeerId>>,
+    ) -> Result<git2::Oid, error::Error> {
+        browser::using(&ctx.peer, branch, |browser| {
+            browser::resolve(browser, revision)
+        })
+    }
+
+    /// The peer whose tags are listed for `peer_id`, the peer of the default branch if `None`.
+    async fn tags_peer(
+        ctx: &context::Unsealed,
+        project_urn: &Urn,
+        peer_id: Option<PeerId>,
+    ) -> Result<Option<PeerId>, error::Error> {
+        match super::http::guard_self_peer_id(&ctx.peer, peer_id) {
+            Some(peer_id) => Ok(Some(peer_id)),
+            None => Ok(
+                radicle_daemon::state::find_default_branch(&ctx.peer, project_urn.clone())
+                    .await?
+                    .remote,
+            ),
+        }
+    }
 
-    use crate::{browser, context, error};
+    /// Stream an archive of the tree of a revision, see [`source::archive::write`].
+    pub async fn archive(
+        project_urn: Urn,
+        query: Option<super::ArchiveQuery>,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let super::ArchiveQuery {
+            format,
+            prefix,
+            peer_id,
+            revision,
+        } = query.unwrap_or_default();
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let revision = super::http::guard_self_revision(&ctx.peer, revision);
+        let format = format.unwrap_or_default();
+        let prefix = prefix.unwrap_or_default();
+
+        let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
+            .await
+            .map_err(error::Error::from)?;
+        // Resolve the revision up front, so that unknown revisions are reported before the
+        // response starts streaming.
+        let commit = resolve_revision(&ctx, branch, revision.clone())?;
+        source::archive::check(&ctx.peer, commit, format, &prefix)?;
+        let etag =
+            super::http::ETag::new(commit, revision.as_ref(), &[format.extension(), &prefix]);
+
+        let name = match prefix.trim_matches('/') {
+            "" => commit.to_string()[..7].to_string(),
+            name => name.replace('/', "-"),
+        };
+        if_none_match.reply(&etag, || {
+            let (sender, receiver) = mpsc::channel(16);
+            let peer = ctx.peer.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut chunks = super::Chunks(sender);
+                let out = std::io::BufWriter::with_capacity(64 * 1024, chunks.clone());
+                if let Err(err) = source::archive::write(&peer, commit, format, &prefix, out) {
+                    chunks.abort(err);
+                }
+            });
+
+            Ok(Response::builder()
+                .header(header::CONTENT_TYPE, format.content_type())
+                .header(
+                    header::CONTENT_DISPOSITION,
+                    super::attachment(&format!("{}.{}", name, format.extension())),
+                )
+                .body(Body::wrap_stream(receiver))
+                .map_err(error::Error::from)?)
+        })
+    }
+
+    /// Fetch the [`source::blame::Blame`] of a blob.
+    pub async fn blame(
+        project_urn: Urn,
+        super::BlameQuery {
+            path,
+            peer_id,
+            revision,
+        }: super::BlameQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let revision = super::http::guard_self_revision(&ctx.peer, revision);
+
+        let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
+            .await
+            .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        let etag = super::http::ETag::new(commit, revision.as_ref(), &[&path]);
+
+        if_none_match.reply(&etag, || {
+            let blame = source::blame::blame(&ctx.peer, branch, revision, &path)?;
+
+            Ok(reply::json(&blame))
+        })
+    }
 
-    /// Fetch a [`radicle_source::Blob`].
+    /// Fetch a window of a [`radicle_source::Blob`], see [`source::window::cut`].
     pub async fn blob(
         project_urn: Urn,
         super::BlobQuery {
@@ -129,33 +428,87 @@ mod handler {
             peer_id,
             revision,
             highlight,
+            start_line,
+            end_line,
+            max_bytes,
         }: super::BlobQuery,
+        if_none_match: IfNoneMatch,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
         let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
         let revision = super::http::guard_self_revision(&ctx.peer, revision);
 
-        let theme = highlight.map(|theme| match theme {
-            super::HighlightTheme::Dark => "base16-ocean.dark",
-            super::HighlightTheme::Light => "base16-ocean.light",
-            super::HighlightTheme::H4x0r => "base16-ocean.h4x0r",
-        });
-
         let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
             .await
             .map_err(error::Error::from)?;
-        let blob = browser::using(&ctx.peer, branch, |browser| {
-            radicle_source::blob::highlighting::blob(browser, revision, &path, theme)
-        })
-        .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        let links = source::tree::links(&ctx.peer, commit, &path).await?;
+
+        let bound = |bound: Option<usize>| bound.map(|bound| bound.to_string()).unwrap_or_default();
+        let mut parts = vec![
+            path.clone(),
+            highlight.clone().unwrap_or_default(),
+            bound(start_line),
+            bound(end_line),
+            bound(max_bytes),
+        ];
+        parts.extend(links.iter().flatten().map(ToString::to_string));
+        let mut etag = super::http::ETag::new(
+            commit,
+            revision.as_ref(),
+            &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        // Whether submodules link to a project depends on the projects that exist locally.
+        if links.is_some() {
+            etag = etag.mutable();
+        }
+
+        if_none_match
+            .reply_async(&etag, || async {
+                // Submodules and symlinks are described rather than read.
+                let special =
+                    source::tree::entry(&ctx.peer, branch.clone(), revision.clone(), &path)?;
+                if let Some(mut entry) = special {
+                    source::tree::link_project(&ctx.peer, &mut entry.info).await?;
+                    return Ok(reply::json(&entry));
+                }
+
+                let mut blob = browser::using(&ctx.peer, branch, |browser| {
+                    radicle_source::blob(browser, revision, &path)
+                })
+                .map_err(error::Error::from)?;
 
-        Ok(reply::json(&blob))
+                let window =
+                    if let radicle_source::blob::BlobContent::Plain(content) = &mut blob.content {
+                        let (lines, window) =
+                            source::window::cut(content, start_line, end_line, max_bytes);
+                        *content = lines;
+                        Some(window)
+                    } else {
+                        None
+                    };
+
+                // Only the window is highlighted, constructs spanning its start may be off.
+                if let (Some(theme), radicle_source::blob::BlobContent::Plain(content)) =
+                    (highlight, &blob.content)
+                {
+                    if let Some(html) = ctx.themes.highlight(content, &path, &theme)? {
+                        blob.content = radicle_source::blob::BlobContent::Html(html);
+                    }
+                }
+
+                Ok::<_, Rejection>(reply::json(&source::window::Blob { blob, window }))
+            })
+            .await
     }
 
     /// Fetch the list [`radicle_source::Branch`].
+    ///
+    /// Listing the branches is cheap, so the response is tagged with the list itself.
     pub async fn branches(
         project_urn: Urn,
         super::BranchQuery { peer_id }: super::BranchQuery,
+        if_none_match: IfNoneMatch,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
         let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
@@ -167,135 +520,778 @@ mod handler {
             radicle_source::branches(browser, RefScope::from(peer_id))
         })
         .map_err(error::Error::from)?;
+        let etag = super::http::ETag::of(&branches);
 
-        Ok(reply::json(&branches))
+        if_none_match.reply(&etag, || Ok(reply::json(&branches)))
     }
 
-    /// Fetch a [`radicle_source::Commit`].
+    /// Fetch a [`radicle_source::Commit`] together with the branches containing it.
+    ///
+    /// The commit is looked up in the namespace of the given peer or, if it isn't given, of the
+    /// default branch. If it can't be found there, the namespaces of all peers with a branch or a
+    /// tag containing the commit are tried, see [`source::commit::publishers`]. Abbreviated ids are
+    /// resolved first, see [`source::resolve::commit`].
+    ///
+    /// The branches containing the commit can change, so the response is tagged with them and
+    /// clients revalidate it.
     pub async fn commit(
         project_urn: Urn,
-        sha1: Oid,
+        sha1: String,
+        query: Option<super::CommitQuery>,
+        if_none_match: IfNoneMatch,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
-        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, project_urn)
+        let super::CommitQuery { peer_id } = query.unwrap_or_default();
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let sha1 = source::resolve::commit(&ctx.peer, &sha1)?;
+        let contained_in = source::commit::containing(&ctx.peer, &project_urn, sha1)?;
+        let etag = super::http::ETag::new(
+            sha1,
+            None,
+            &[
+                &peer_id
+                    .map(|peer_id| peer_id.to_string())
+                    .unwrap_or_default(),
+                &serde_json::to_string(&contained_in).unwrap_or_default(),
+            ],
+        );
+
+        if_none_match
+            .reply_async(&etag, || async {
+                let commit = find_commit(&ctx, &project_urn, peer_id, sha1).await?;
+
+                Ok::<_, Rejection>(reply::json(&super::Commit {
+                    commit,
+                    contained_in,
+                }))
+            })
             .await
-            .map_err(error::Error::from)?;
-        let commit = browser::using(&ctx.peer, default_branch, |mut browser| {
-            radicle_source::commit(&mut browser, *sha1)
-        })
-        .map_err(error::Error::from)?;
+    }
+
+    /// Look up `sha1` for [`commit`].
+    async fn find_commit(
+        ctx: &context::Unsealed,
+        project_urn: &Urn,
+        peer_id: Option<PeerId>,
+        sha1: git2::Oid,
+    ) -> Result<radicle_source::Commit, error::Error> {
+        let branch = match peer_id {
+            Some(_) => {
+                radicle_daemon::state::get_branch(&ctx.peer, project_urn.clone(), peer_id, None)
+                    .await
+            },
+            None => {
+                radicle_daemon::state::find_default_branch(&ctx.peer, project_urn.clone()).await
+            },
+        }?;
+        let mut result = browser::using(&ctx.peer, branch, |mut browser| {
+            radicle_source::commit(&mut browser, sha1)
+        });
 
-        Ok(reply::json(&commit))
+        if result.is_err() {
+            for remote in source::commit::publishers(&ctx.peer, project_urn, sha1)? {
+                if remote == peer_id {
+                    continue;
+                }
+                let branch = match radicle_daemon::state::get_branch(
+                    &ctx.peer,
+                    project_urn.clone(),
+                    remote,
+                    None,
+                )
+                .await
+                {
+                    Ok(branch) => branch,
+                    Err(radicle_daemon::state::Error::MissingRef { .. }) => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                result = browser::using(&ctx.peer, branch, |mut browser| {
+                    radicle_source::commit(&mut browser, sha1)
+                });
+                if result.is_ok() {
+                    break;
+                }
+            }
+        }
+
+        result
     }
 
-    /// Fetch the list of [`radicle_source::Commit`] from a branch.
+    /// Fetch a page of the history of a revision, see [`source::history::commits`].
+    ///
+    /// The stats of the first page count the branches, which can change while the revision
+    /// doesn't, so its tag also covers the branches.
     pub async fn commits(
         ctx: context::Unsealed,
         project_urn: Urn,
-        super::CommitsQuery { revision }: super::CommitsQuery,
+        super::CommitsQuery {
+            revision,
+            limit,
+            after,
+            author,
+            since,
+            until,
+            path,
+        }: super::CommitsQuery,
+        if_none_match: IfNoneMatch,
     ) -> Result<impl Reply, Rejection> {
         let revision = super::http::guard_self_revision(&ctx.peer, revision);
+        let filter = source::history::Filter {
+            author,
+            since,
+            until,
+            path,
+        };
+        let page = source::history::Page { limit, after };
 
         let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, project_urn)
             .await
             .map_err(error::Error::from)?;
-        let commits = browser::using(&ctx.peer, default_branch, |mut browser| {
-            radicle_source::commits(&mut browser, revision)
-        })
-        .map_err(error::Error::from)?;
+        let (head, branches) = browser::using(&ctx.peer, default_branch.clone(), |browser| {
+            let head = browser::resolve(browser, revision.clone())?;
+            let branches = match page.after {
+                Some(_) => Vec::new(),
+                None => radicle_source::branches(browser, RefScope::All)?,
+            };
+            Ok((head, branches))
+        })?;
+        let mut etag = super::http::ETag::new(
+            head,
+            revision.as_ref(),
+            &[
+                &page
+                    .limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default(),
+                &page
+                    .after
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                filter.author.as_deref().unwrap_or_default(),
+                &filter
+                    .since
+                    .map(|since| since.to_string())
+                    .unwrap_or_default(),
+                &filter
+                    .until
+                    .map(|until| until.to_string())
+                    .unwrap_or_default(),
+                filter.path.as_deref().unwrap_or_default(),
+                &serde_json::to_string(&branches).unwrap_or_default(),
+            ],
+        );
+        if page.after.is_none() {
+            etag = etag.mutable();
+        }
 
-        Ok(reply::json(&commits))
+        if_none_match.reply(&etag, || {
+            let commits =
+                source::history::commits(&ctx.peer, default_branch, revision, &filter, &page)?;
+
+            Ok(reply::json(&commits))
+        })
     }
 
-    /// Fetch the list [`radicle_source::Branch`] for a local repository.
-    #[allow(clippy::unused_async)]
-    pub async fn local_state(
-        commits_query: super::LocalStateQuery,
+    /// Compare two revisions of a project, see [`source::compare::compare`].
+    pub async fn compare(
+        project_urn: Urn,
+        super::CompareQuery { base, head }: super::CompareQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
-        let state = radicle_source::local_state(&commits_query.path, "master")
+        let base = super::http::guard_self_revision(&ctx.peer, Some(base));
+        let head = super::http::guard_self_revision(&ctx.peer, Some(head));
+
+        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, project_urn)
+            .await
             .map_err(error::Error::from)?;
+        let (base_commit, head_commit) =
+            browser::using(&ctx.peer, default_branch.clone(), |browser| {
+                let base = browser::resolve(browser, base.clone())?;
+                let head = browser::resolve(browser, head.clone())?;
+                Ok((base, head))
+            })?;
+        let mut etag =
+            super::http::ETag::new(head_commit, head.as_ref(), &[&base_commit.to_string()]);
+        // The comparison only stays the same for good if both revisions are commit ids.
+        if !matches!(base, Some(radicle_source::Revision::Sha { .. })) {
+            etag = etag.mutable();
+        }
+
+        if_none_match.reply(&etag, || {
+            let comparison = source::compare::compare(&ctx.peer, default_branch, base, head)?;
+
+            Ok(reply::json(&comparison))
+        })
+    }
+
+    /// Build the commit graph of the branches of several peers, see [`source::graph::graph`]. The
+    /// response is tagged with the branches it is built from.
+    pub async fn graph(
+        project_urn: Urn,
+        query: Option<super::GraphQuery>,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let super::GraphQuery { peer_ids, limit } = query.unwrap_or_default();
+        let peers = peer_ids.map(|peer_ids| {
+            peer_ids
+                .into_iter()
+                .map(|peer_id| super::http::guard_self_peer_id(&ctx.peer, Some(peer_id)))
+                .collect::<Vec<_>>()
+        });
+
+        let tips = source::graph::tips(&ctx.peer, &project_urn, peers.as_deref())?;
+        let parts = tips
+            .iter()
+            .map(|(branch, tip)| {
+                format!(
+                    "{}/{} {}",
+                    branch
+                        .peer_id
+                        .map(|peer_id| peer_id.to_string())
+                        .unwrap_or_default(),
+                    branch.name,
+                    tip
+                )
+            })
+            .chain(limit.map(|limit| limit.to_string()))
+            .collect::<Vec<_>>();
+        let etag = super::http::ETag::new(
+            git2::Oid::zero(),
+            None,
+            &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
 
-        Ok(reply::json(&state))
+        if_none_match.reply(&etag, || {
+            let graph = source::graph::graph(&ctx.peer, tips, limit)?;
+
+            Ok(reply::json(&graph))
+        })
     }
 
-    /// Fetch the list [`radicle_source::Tag`].
-    pub async fn tags(
+    /// Fetch the commits that changed a path, see [`source::history::file`].
+    pub async fn history(
         project_urn: Urn,
-        _query: super::TagQuery,
+        super::HistoryQuery {
+            path,
+            peer_id,
+            revision,
+        }: super::HistoryQuery,
+        if_none_match: IfNoneMatch,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
-        let branch = radicle_daemon::state::find_default_branch(&ctx.peer, project_urn)
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let revision = super::http::guard_self_revision(&ctx.peer, revision);
+
+        let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
             .await
             .map_err(error::Error::from)?;
-        let tags = browser::using(&ctx.peer, branch, |browser| radicle_source::tags(browser))
-            .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        let etag = super::http::ETag::new(commit, revision.as_ref(), &[&path]);
+
+        if_none_match.reply(&etag, || {
+            let changes = source::history::file(&ctx.peer, branch, revision, &path)?;
 
-        Ok(reply::json(&tags))
+            Ok(reply::json(&changes))
+        })
     }
 
-    /// Fetch a [`radicle_source::Tree`].
-    pub async fn tree(
+    /// Fetch the [`source::working_copy::Status`] of a local repository.
+    #[allow(clippy::unused_async)]
+    pub async fn local_state(
+        super::LocalStateQuery { path }: super::LocalStateQuery,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let status = source::working_copy::status(&ctx.peer, Path::new(&path))?;
+
+        Ok(reply::json(&status))
+    }
+
+    /// Fetch the raw content of a blob. Supports fetching a single range of bytes through the
+    /// `Range` header.
+    pub async fn raw(
         project_urn: Urn,
-        super::TreeQuery {
-            prefix,
+        super::RawQuery {
+            path,
             peer_id,
             revision,
-        }: super::TreeQuery,
+        }: super::RawQuery,
+        range: Option<String>,
+        if_none_match: IfNoneMatch,
         ctx: context::Unsealed,
     ) -> Result<impl Reply, Rejection> {
         let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
         let revision = super::http::guard_self_revision(&ctx.peer, revision);
+
         let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
             .await
             .map_err(error::Error::from)?;
-        let tree = browser::using(&ctx.peer, branch, |mut browser| {
-            radicle_source::tree(&mut browser, revision, prefix)
-        })
-        .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        // Ranges are slices of the same representation, so they share its tag.
+        let etag = super::http::ETag::new(commit, revision.as_ref(), &[&path]);
 
-        Ok(reply::json(&tree))
+        if_none_match.reply(&etag, || raw_response(&ctx, branch, revision, &path, range))
     }
-}
 
-/// Query parameters for [`handler::local_state`]
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LocalStateQuery {
-    /// Path to the repository to get the local state of
-    path: String,
-}
+    /// Build the response of [`raw`], honouring the `range` header.
+    fn raw_response(
+        ctx: &context::Unsealed,
+        branch: Reference<Single>,
+        revision: Option<radicle_source::Revision<PeerId>>,
+        path: &str,
+        range: Option<String>,
+    ) -> Result<Response<Body>, Rejection> {
+        let raw = source::raw::raw(&ctx.peer, branch, revision, path)?;
+
+        let size = raw.content.len();
+        let (status, content_range, content) = match super::byte_range(range.as_deref(), size) {
+            super::ByteRange::Full => (StatusCode::OK, None, raw.content),
+            super::ByteRange::Partial(range) => (
+                StatusCode::PARTIAL_CONTENT,
+                Some(format!("bytes {}-{}/{}", range.start, range.end - 1, size)),
+                raw.content[range].to_vec(),
+            ),
+            super::ByteRange::Unsatisfiable => (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                Some(format!("bytes */{}", size)),
+                Vec::new(),
+            ),
+        };
 
-/// Bundled query params to pass to the commits handler.
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CommitsQuery {
-    /// Revision to query at.
-    revision: Option<radicle_source::Revision<PeerId>>,
-}
+        let mut response = Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, raw.content_type)
+            .header(header::CONTENT_LENGTH, content.len())
+            .header(header::ACCEPT_RANGES, "bytes")
+            // Never let content of a project execute in the context of the API.
+            .header(header::CONTENT_SECURITY_POLICY, "sandbox")
+            .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+        if let Some(content_range) = content_range {
+            response = response.header(header::CONTENT_RANGE, content_range);
+        }
+
+        Ok(response
+            .body(Body::from(content))
+            .map_err(error::Error::from)?)
+    }
 
-/// Bundled query params to pass to the blob handler.
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BlobQuery {
-    /// Location of the blob in tree.
-    path: String,
-    /// PeerId to scope the query by.
-    peer_id: Option<PeerId>,
-    /// Revision to query at.
-    revision: Option<radicle_source::Revision<PeerId>>,
-    /// Whether or not to syntax highlight the blob.
-    highlight: Option<HighlightTheme>,
-}
+    /// Fetch the README of a directory rendered to HTML, see [`source::readme::render`].
+    ///
+    /// Relative links and images in the README point to the blob, tree and raw endpoints of the
+    /// same project and revision.
+    pub async fn readme(
+        project_urn: Urn,
+        query: Option<super::ReadmeQuery>,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let query = query.unwrap_or_default();
+        // The links in the README repeat the query, so all of it goes into the tag.
+        let parts = serde_qs::to_string(&query).unwrap_or_default();
+        let super::ReadmeQuery {
+            prefix,
+            peer_id,
+            revision,
+        } = query;
+        let base = format!("http://{}/v1/source", ctx.http_listen);
+        let link = |endpoint: &str, query: String| {
+            format!("{}/{}/{}?{}", base, endpoint, project_urn, query)
+        };
+        let rewrite = |target: source::readme::Target| match target {
+            source::readme::Target::Blob(path) => link(
+                "blob",
+                serde_qs::to_string(&super::BlobQuery {
+                    path: path.to_string(),
+                    peer_id,
+                    revision: revision.clone(),
+                    highlight: None,
+                    start_line: None,
+                    end_line: None,
+                    max_bytes: None,
+                })
+                .unwrap_or_default(),
+            ),
+            source::readme::Target::Tree(path) => link(
+                "tree",
+                serde_qs::to_string(&super::TreeQuery {
+                    prefix: Some(path.to_string()),
+                    peer_id,
+                    revision: revision.clone(),
+                })
+                .unwrap_or_default(),
+            ),
+            source::readme::Target::Image(path) => link(
+                "raw",
+                serde_qs::to_string(&super::RawQuery {
+                    path: path.to_string(),
+                    peer_id,
+                    revision: revision.clone(),
+                })
+                .unwrap_or_default(),
+            ),
+        };
 
-/// A query param for [`handler::branches`].
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BranchQuery {
-    /// PeerId to scope the query by.
-    peer_id: Option<PeerId>,
-}
+        let branch = radicle_daemon::state::get_branch(
+            &ctx.peer,
+            project_urn.clone(),
+            super::http::guard_self_peer_id(&ctx.peer, peer_id),
+            None,
+        )
+        .await
+        .map_err(error::Error::from)?;
+        let guarded = super::http::guard_self_revision(&ctx.peer, revision.clone());
+        let commit = resolve_revision(&ctx, branch.clone(), guarded.clone())?;
+        let etag = super::http::ETag::new(commit, guarded.as_ref(), &[&parts]);
+
+        if_none_match.reply(&etag, || {
+            let readme = source::readme::render(
+                &ctx.peer,
+                branch,
+                guarded,
+                prefix.as_deref().unwrap_or_default(),
+                rewrite,
+            )?;
+
+            Ok(reply::json(&readme))
+        })
+    }
 
-/// Bundled query params to pass to the tree handler.
+    /// Resolve a revision to the commit it refers to, see [`source::resolve::resolve`].
+    pub async fn resolve(
+        project_urn: Urn,
+        super::ResolveQuery { rev }: super::ResolveQuery,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let resolution = source::resolve::resolve(&ctx.peer, &project_urn, &rev)?;
+
+        Ok(reply::json(&resolution))
+    }
+
+    /// Search the blobs of a revision, see [`source::search::search`].
+    pub async fn search(
+        project_urn: Urn,
+        super::SearchQuery {
+            q,
+            regex,
+            case_sensitive,
+            path,
+            limit,
+            peer_id,
+            revision,
+        }: super::SearchQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let revision = super::http::guard_self_revision(&ctx.peer, revision);
+        let query = source::search::Query {
+            pattern: q,
+            regex: regex.unwrap_or(false),
+            case_sensitive: case_sensitive.unwrap_or(false),
+            path,
+            limit,
+        };
+
+        let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
+            .await
+            .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        let etag = super::http::ETag::new(
+            commit,
+            revision.as_ref(),
+            &[
+                &query.pattern,
+                &query.regex.to_string(),
+                &query.case_sensitive.to_string(),
+                query.path.as_deref().unwrap_or_default(),
+                &query
+                    .limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default(),
+            ],
+        );
+
+        if_none_match.reply(&etag, || {
+            let search = source::search::search(&ctx.peer, branch, revision, &query)?;
+
+            Ok(reply::json(&search))
+        })
+    }
+
+    /// Fetch a single tag with the header of the commit it points to, see
+    /// [`source::tag::get`]. The response is tagged with the tag itself.
+    pub async fn tag(
+        project_urn: Urn,
+        name: Tail,
+        super::TagQuery { peer_id }: super::TagQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let peer_id = tags_peer(&ctx, &project_urn, peer_id).await?;
+        let name = percent_encoding::percent_decode_str(name.as_str()).decode_utf8_lossy();
+        let tag = source::tag::get(&ctx.peer, &project_urn, peer_id, &name)?;
+        let etag = super::http::ETag::of(&tag);
+
+        if_none_match.reply(&etag, || Ok(reply::json(&tag)))
+    }
+
+    /// Fetch the tags of a peer, see [`source::tag::list`].
+    ///
+    /// Listing the tags is cheap, so the response is tagged with the list itself.
+    pub async fn tags(
+        project_urn: Urn,
+        super::TagQuery { peer_id }: super::TagQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let peer_id = tags_peer(&ctx, &project_urn, peer_id).await?;
+        let tags = source::tag::list(&ctx.peer, &project_urn, peer_id)?;
+        let etag = super::http::ETag::of(&tags);
+
+        if_none_match.reply(&etag, || Ok(reply::json(&tags)))
+    }
+
+    /// List the syntax highlighting themes, see [`source::theme::Registry::list`].
+    pub async fn themes(ctx: context::Unsealed) -> Result<impl Reply, Rejection> {
+        Ok(reply::json(&ctx.themes.list()))
+    }
+
+    /// Fetch a [`source::tree::Tree`].
+    pub async fn tree(
+        project_urn: Urn,
+        super::TreeQuery {
+            prefix,
+            peer_id,
+            revision,
+        }: super::TreeQuery,
+        if_none_match: IfNoneMatch,
+        ctx: context::Unsealed,
+    ) -> Result<impl Reply, Rejection> {
+        let peer_id = super::http::guard_self_peer_id(&ctx.peer, peer_id);
+        let revision = super::http::guard_self_revision(&ctx.peer, revision);
+        let branch = radicle_daemon::state::get_branch(&ctx.peer, project_urn, peer_id, None)
+            .await
+            .map_err(error::Error::from)?;
+        let commit = resolve_revision(&ctx, branch.clone(), revision.clone())?;
+        let links =
+            source::tree::links(&ctx.peer, commit, prefix.as_deref().unwrap_or_default()).await?;
+
+        let mut parts = vec![prefix.clone().unwrap_or_default()];
+        parts.extend(links.iter().flatten().map(ToString::to_string));
+        let mut etag = super::http::ETag::new(
+            commit,
+            revision.as_ref(),
+            &parts.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        // Whether submodules link to a project depends on the projects that exist locally.
+        if links.is_some() {
+            etag = etag.mutable();
+        }
+
+        if_none_match
+            .reply_async(&etag, || async {
+                let mut tree = source::tree::tree(&ctx.peer, branch, revision, prefix)?;
+                // The cached tree is only copied if there are submodules to link.
+                if links.is_some() {
+                    for entry in &mut Arc::make_mut(&mut tree).entries {
+                        source::tree::link_project(&ctx.peer, &mut entry.info).await?;
+                    }
+                }
+
+                Ok::<_, Rejection>(reply::json(&*tree))
+            })
+            .await
+    }
+}
+
+/// Query parameters for [`handler::local_state`]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalStateQuery {
+    /// Path to the repository to get the local state of
+    path: String,
+}
+
+/// Bundled query params to pass to the commit handler.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitQuery {
+    /// PeerId to look the commit up for first.
+    peer_id: Option<PeerId>,
+}
+
+/// A [`radicle_source::Commit`] and the branches of all peers containing it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Commit {
+    /// The commit itself.
+    #[serde(flatten)]
+    commit: radicle_source::Commit,
+    /// Branches of the local peer and tracked remotes that contain the commit.
+    contained_in: Vec<source::commit::Branch>,
+}
+
+/// Bundled query params to pass to the commits handler.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitsQuery {
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+    /// Maximum number of commits to return.
+    limit: Option<usize>,
+    /// Cursor of the previous page, the `next` field of its response.
+    after: Option<Oid>,
+    /// Only return commits where the author name or email contains this string.
+    author: Option<String>,
+    /// Only return commits committed at or after this time, in seconds since the epoch.
+    since: Option<i64>,
+    /// Only return commits committed at or before this time, in seconds since the epoch.
+    until: Option<i64>,
+    /// Only return commits that changed this path.
+    path: Option<String>,
+}
+
+/// Bundled query params to pass to the compare handler.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareQuery {
+    /// Revision to compare against.
+    base: radicle_source::Revision<PeerId>,
+    /// Revision whose changes over `base` are of interest.
+    head: radicle_source::Revision<PeerId>,
+}
+
+/// Bundled query params to pass to the archive handler.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveQuery {
+    /// Format of the archive, a tarball if `None`.
+    format: Option<source::archive::Format>,
+    /// Prefix prepended to every path in the archive.
+    prefix: Option<String>,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled query params to pass to the blame handler.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlameQuery {
+    /// Location of the blob in tree.
+    path: String,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled query params to pass to the blob handler.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobQuery {
+    /// Location of the blob in tree.
+    path: String,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+    /// Name of the theme to syntax highlight the blob with, see [`source::theme::Registry::list`].
+    highlight: Option<String>,
+    /// First line to return, starting at 1.
+    start_line: Option<usize>,
+    /// Last line to return.
+    end_line: Option<usize>,
+    /// Maximum number of bytes of content to return.
+    max_bytes: Option<usize>,
+}
+
+/// Bundled query params to pass to the history handler.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    /// Location of the entry in the tree.
+    path: String,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled query params to pass to the raw handler.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawQuery {
+    /// Location of the blob in tree.
+    path: String,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled query params to pass to the readme handler.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadmeQuery {
+    /// Directory to look for the README in, the root of the tree if `None`.
+    prefix: Option<String>,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// Bundled query params to pass to the graph handler.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQuery {
+    /// Peers whose branches span the graph, all peers if `None`.
+    peer_ids: Option<Vec<PeerId>>,
+    /// Maximum number of commits to return.
+    limit: Option<usize>,
+}
+
+/// A query param for [`handler::resolve`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveQuery {
+    /// The revision to resolve, see [`source::resolve::resolve`].
+    rev: String,
+}
+
+/// Bundled query params to pass to the search handler.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    /// Pattern to search for.
+    q: String,
+    /// Interpret `q` as a regular expression, defaults to a literal search.
+    regex: Option<bool>,
+    /// Match the case of `q`, defaults to ignoring case.
+    case_sensitive: Option<bool>,
+    /// Only search below this path.
+    path: Option<String>,
+    /// Maximum number of matching lines to return.
+    limit: Option<usize>,
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+    /// Revision to query at.
+    revision: Option<radicle_source::Revision<PeerId>>,
+}
+
+/// A query param for [`handler::branches`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchQuery {
+    /// PeerId to scope the query by.
+    peer_id: Option<PeerId>,
+}
+
+/// Bundled query params to pass to the tree handler.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TreeQuery {
@@ -307,24 +1303,121 @@ pub struct TreeQuery {
     revision: Option<radicle_source::Revision<PeerId>>,
 }
 
-/// A query param for [`handler::tags`].
+/// A query param for [`handler::tag`] and [`handler::tags`].
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TagQuery {
-    /// PeerId to scope the query by.
+    /// PeerId to scope the query by, the peer of the default branch if `None`.
     peer_id: Option<PeerId>,
 }
 
-/// Syntax highlighting theme
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub enum HighlightTheme {
-    /// A dark theme.
-    Dark,
-    /// A light theme.
-    Light,
-    /// A h4x0r theme.
-    H4x0r,
+/// Forwards everything written to it as chunks of a streamed response body.
+#[derive(Clone)]
+struct Chunks(mpsc::Sender<Result<Vec<u8>, io::Error>>);
+
+impl Chunks {
+    /// Terminate the response body with `err`, the client sees the response being cut off.
+    fn abort(&mut self, err: error::Error) {
+        let err = io::Error::new(io::ErrorKind::Other, err.to_string());
+        executor::block_on(self.0.send(Err(err))).ok();
+    }
+}
+
+impl io::Write for Chunks {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        executor::block_on(self.0.send(Ok(buf.to_vec())))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body was dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The part of a blob requested through a `Range` header.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    /// No or an unsupported range was requested, the whole blob is served.
+    Full,
+    /// The given range of bytes is served.
+    Partial(std::ops::Range<usize>),
+    /// The requested range lies outside of the blob.
+    Unsatisfiable,
+}
+
+/// Match the value of a `Range` header against a blob of `size` bytes.
+///
+/// Only a single range in `bytes` is supported. Following RFC 7233, headers requesting multiple
+/// ranges or that can't be parsed are ignored and the whole blob is served.
+fn byte_range(header: Option<&str>, size: usize) -> ByteRange {
+    let spec = match header.and_then(|header| header.trim().strip_prefix("bytes=")) {
+        Some(spec) if !spec.contains(',') => spec,
+        _ => return ByteRange::Full,
+    };
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => return ByteRange::Full,
+    };
+
+    let range = match (start.parse::<usize>(), end.parse::<usize>()) {
+        // `bytes=-<suffix>` requests the last `suffix` bytes.
+        (Err(_), Ok(suffix)) if start.is_empty() => {
+            if suffix == 0 {
+                return ByteRange::Unsatisfiable;
+            }
+            size.saturating_sub(suffix)..size
+        },
+        (Ok(start), Err(_)) if end.is_empty() => start..size,
+        (Ok(start), Ok(end)) if start <= end => start..end.saturating_add(1).min(size),
+        _ => return ByteRange::Full,
+    };
+
+    if range.start >= size {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(range)
+    }
+}
+
+/// Characters allowed unencoded in RFC 5987 extended parameter values, all others are
+/// percent-encoded.
+const ATTR_CHAR: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// Value of a `Content-Disposition` header offering the response as a download named `filename`.
+///
+/// The name is passed as RFC 5987 `filename*` parameter. Clients that don't support it fall back
+/// to the `filename` parameter, which has all characters but printable ASCII except quotes and
+/// backslashes replaced with `_`.
+fn attachment(filename: &str) -> String {
+    let fallback = filename
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() || c == ' ') && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        percent_encoding::utf8_percent_encode(filename, ATTR_CHAR)
+    )
 }
 
 #[allow(clippy::non_ascii_literal, clippy::unwrap_used)]
@@ -337,9 +1430,122 @@ mod test {
     use warp::{http::StatusCode, test::request};
 
     use link_identities::git::Urn;
-    use radicle_source::surf::vcs::git::RefScope;
+    use radicle_source::surf::vcs::git::{git2, RefScope};
+
+    use crate::{browser, context, error, http, source};
+
+    #[tokio::test]
+    async fn archive() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let query = super::ArchiveQuery {
+            format: None,
+            prefix: Some("platinum/".to_string()),
+            peer_id: None,
+            revision: None,
+        };
+        let path = format!("/archive/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/gzip");
+        assert_eq!(
+            res.headers()["content-disposition"],
+            "attachment; filename=\"platinum.tar.gz\"; filename*=UTF-8''platinum.tar.gz"
+        );
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(res.body().as_ref()));
+        let paths = archive
+            .entries()?
+            .map(|entry| Ok(entry?.path()?.display().to_string()))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        assert!(paths.contains(&"platinum/text/arrows.txt".to_string()));
+        assert!(paths.iter().all(|path| path.starts_with("platinum/")));
+
+        // Prefixes can't break out of the header.
+        let query = super::ArchiveQuery {
+            format: Some(source::archive::Format::Zip),
+            prefix: Some("plätinum\"\r\nx: y/".to_string()),
+            ..query
+        };
+        let path = format!("/archive/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/zip");
+        assert_eq!(
+            res.headers()["content-disposition"],
+            "attachment; filename=\"pl_tinum___x: y.zip\"; \
+             filename*=UTF-8''pl%C3%A4tinum%22%0D%0Ax%3A%20y.zip"
+        );
+        assert!(res.body().starts_with(b"PK\x03\x04"));
+
+        // Entries can't leave the directory the archive is extracted to.
+        let query = super::ArchiveQuery {
+            prefix: Some("../x/".to_string()),
+            ..query
+        };
+        let path = format!("/archive/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::BAD_REQUEST, |have| {
+            assert_eq!(have["variant"], json!("INVALID_PATH"));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blame() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let revision = radicle_source::Revision::Branch {
+            name: "master".to_string(),
+            peer_id: None,
+        };
+        let query = super::BlameQuery {
+            path: "text/arrows.txt".to_string(),
+            peer_id: None,
+            revision: Some(revision),
+        };
+        let path = format!("/blame/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have,
+                json!({
+                    "hunks": [{
+                        "startLine": 1,
+                        "lines": 7,
+                        "sha1": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                        "path": Value::Null,
+                    }],
+                    "commits": {
+                        "1e0206da8571ca71c51c91154e2fee376e09b4e7": {
+                            "sha1": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                            "author": {
+                                "name": "Rūdolfs Ošiņš",
+                                "email": "rudolfs@osins.org",
+                            },
+                            "committer": {
+                                "name": "Rūdolfs Ošiņš",
+                                "email": "rudolfs@osins.org",
+                            },
+                            "summary": "Add text files",
+                            "description": "",
+                            "committerTime": 1_575_283_425,
+                        },
+                    },
+                })
+            );
+        });
 
-    use crate::{browser, context, error, http};
+        Ok(())
+    }
 
     #[allow(clippy::too_many_lines)]
     #[tokio::test]
@@ -365,6 +1571,9 @@ mod test {
             peer_id: None,
             revision: Some(revision.clone()),
             highlight: None,
+            start_line: None,
+            end_line: None,
+            max_bytes: None,
         };
 
         let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
@@ -373,7 +1582,7 @@ mod test {
         let res = request().method("GET").path(&path).reply(&api).await;
 
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
+            assert_eq!(without_window(&have), json!(want));
             assert_eq!(
                 have,
                 json!({
@@ -406,6 +1615,11 @@ mod test {
                         },
                     },
                     "path": "text/arrows.txt",
+                    "startLine": 1,
+                    "endLine": 7,
+                    "totalBytes": 240,
+                    "totalLines": 7,
+                    "truncated": false,
                 })
             );
         });
@@ -423,6 +1637,9 @@ mod test {
             peer_id: None,
             revision: Some(revision),
             highlight: None,
+            start_line: None,
+            end_line: None,
+            max_bytes: None,
         };
 
         let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
@@ -480,6 +1697,9 @@ mod test {
             peer_id: None,
             revision: Some(revision.clone()),
             highlight: None,
+            start_line: None,
+            end_line: None,
+            max_bytes: None,
         };
 
         // Get ASCII blob.
@@ -498,158 +1718,959 @@ mod test {
             radicle_source::blob(&mut browser, Some(revision), path)
         })?;
 
-        http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
-        });
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(without_window(&have), json!(want));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blob_window() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let query = super::BlobQuery {
+            path: "text/arrows.txt".to_string(),
+            peer_id: None,
+            revision: Some(radicle_source::Revision::Branch {
+                name: "master".to_string(),
+                peer_id: None,
+            }),
+            highlight: None,
+            start_line: Some(5),
+            end_line: Some(7),
+            max_bytes: Some(72),
+        };
+        let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+
+        let res = request().method("GET").path(&path).reply(&api).await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have["content"],
+                json!("..;;;;;..    ..;;;;;..    ..;;;;;..\n ':::::'      ':::::'      ':::::'\n")
+            );
+            assert_eq!(have["startLine"], json!(5));
+            assert_eq!(have["endLine"], json!(6));
+            assert_eq!(have["totalBytes"], json!(240));
+            assert_eq!(have["totalLines"], json!(7));
+            assert_eq!(have["truncated"], json!(true));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn branches() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+        let urn = replicate_platinum(&ctx).await?;
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/branches/{}", urn))
+            .reply(&api)
+            .await;
+
+        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
+        let want = browser::using(&ctx.peer, default_branch, |browser| {
+            radicle_source::branches(browser, RefScope::All)
+        })?;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, json!(want));
+            assert_eq!(have, json!(["dev", "master"]));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[allow(clippy::indexing_slicing)]
+    async fn commit() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let sha1 = radicle_git_ext::Oid::try_from("3873745c8f6ffb45c990eb23b491d4b4b6182f95")?;
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/commit/{}/{}", urn, sha1))
+            .reply(&api)
+            .await;
+
+        let contained_in = source::commit::containing(&ctx.peer, &urn, *sha1)?;
+        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
+        let want = browser::using(&ctx.peer, default_branch, |mut browser| {
+            radicle_source::commit::header(&mut browser, *sha1)
+        })?;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["header"], json!(want));
+            assert_eq!(have["containedIn"], json!(contained_in));
+            assert!(have["containedIn"]
+                .as_array()
+                .unwrap()
+                .contains(&json!({ "peerId": null, "name": "master" })));
+            assert_eq!(
+                have["header"],
+                json!({
+                    "sha1": sha1,
+                    "author": {
+                        "name": "Fintan Halpenny",
+                        "email": "fintan.halpenny@gmail.com",
+                    },
+                    "committer": {
+                        "email": "noreply@github.com",
+                        "name": "GitHub",
+                    },
+                    "summary": "Extend the docs (#2)",
+                    "description": "I want to have files under src that have separate commits.\r\nThat way src\'s latest commit isn\'t the same as all its files, instead it\'s the file that was touched last.",
+                    "committerTime": 1_578_309_972,
+                }),
+            );
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/commit/{}/3873745", urn))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["header"]["sha1"], json!(sha1));
+            assert_eq!(have["header"]["summary"], json!("Extend the docs (#2)"));
+        });
+
+        for missing in &["0000000000000000000000000000000000000001", "0000001"] {
+            let res = request()
+                .method("GET")
+                .path(&format!("/commit/{}/{}", urn, missing))
+                .reply(&api)
+                .await;
+            http::test::assert_response(&res, StatusCode::NOT_FOUND, |have| {
+                assert_eq!(have["variant"], json!("NOT_FOUND"));
+            });
+        }
+
+        // Commit on a new branch until two commits share the first four digits of their ids.
+        let prefix = source::refs_prefix(&urn, None);
+        let ambiguous = browser::using_repository(&ctx.peer, |repo| {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push_glob(&format!("{}*", prefix))?;
+            let mut seen = std::collections::HashSet::new();
+            for oid in revwalk {
+                seen.insert(oid?.to_string()[..4].to_string());
+            }
+
+            let signature = git2::Signature::new(
+                "cloudhead",
+                "cloudhead@radicle.xyz",
+                &git2::Time::new(1_600_000_000, 0),
+            )?;
+            let mut parent = repo.find_commit(*sha1)?;
+            let tree = parent.tree()?;
+            for i in 0.. {
+                let oid = repo.commit(
+                    None,
+                    &signature,
+                    &signature,
+                    &format!("Commit {}", i),
+                    &tree,
+                    &[&parent],
+                )?;
+                parent = repo.find_commit(oid)?;
+                let short = oid.to_string()[..4].to_string();
+                if !seen.insert(short.clone()) {
+                    repo.reference(&format!("{}heads/collide", prefix), oid, false, "collide")?;
+                    return Ok(short);
+                }
+            }
+            unreachable!()
+        })?;
+
+        let res = request()
+            .method("GET")
+            .path(&format!("/commit/{}/{}", urn, ambiguous))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::BAD_REQUEST, |have| {
+            assert_eq!(have["variant"], json!("AMBIGUOUS_REVISION"));
+        });
+
+        Ok(())
+    }
+
+    #[allow(clippy::indexing_slicing)]
+    #[tokio::test]
+    async fn commits() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+
+        let query = super::CommitsQuery {
+            revision: Some(radicle_source::Revision::Branch {
+                name: "dev".to_string(),
+                peer_id: None,
+            }),
+            limit: None,
+            after: None,
+            author: None,
+            since: None,
+            until: None,
+            path: None,
+        };
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/commits/{}?{}",
+                urn,
+                serde_qs::to_string(&query).unwrap(),
+            ))
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["headers"].as_array().unwrap().len(), 8);
+            assert!(have["headers"][0]["sha1"]
+                .as_str()
+                .unwrap()
+                .starts_with("27acd68"));
+            assert_eq!(
+                have["headers"][0]["summary"],
+                json!("Commit on the dev branch")
+            );
+            assert_eq!(have["stats"]["commits"], json!(8));
+            assert_eq!(have["stats"]["branches"], json!(2));
+            assert_eq!(have["next"], Value::Null);
+        });
+
+        Ok(())
+    }
+
+    #[allow(clippy::indexing_slicing)]
+    #[tokio::test]
+    async fn commits_paginated() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+
+        let revision = radicle_source::Revision::Branch {
+            name: "master".to_string(),
+            peer_id: None,
+        };
+        let mut query = super::CommitsQuery {
+            revision: Some(revision),
+            limit: None,
+            after: None,
+            author: None,
+            since: None,
+            until: None,
+            path: None,
+        };
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let all = request().method("GET").path(&path).reply(&api).await;
+        let all: Value = serde_json::from_slice(all.body())?;
+        assert_eq!(all["headers"].as_array().unwrap().len(), 15);
+
+        query.limit = Some(2);
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        let mut next = None;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["headers"].as_array().unwrap().len(), 2);
+            assert!(have["headers"][0]["sha1"]
+                .as_str()
+                .unwrap()
+                .starts_with("a0dd912"));
+            assert_eq!(
+                have["headers"][0]["summary"],
+                json!("Add files with special characters in their filenames (#5)")
+            );
+            assert_eq!(have["headers"][1], all["headers"][1]);
+            assert_eq!(have["stats"]["commits"], json!(15));
+            assert_eq!(have["next"], have["headers"][1]["sha1"]);
+            next = Some(serde_json::from_value(have["next"].clone()).unwrap());
+        });
+
+        // Later pages continue after the cursor and leave out the stats.
+        query.after = next;
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["headers"][0], all["headers"][2]);
+            assert_eq!(have["headers"][1], all["headers"][3]);
+            assert_eq!(have["stats"], Value::Null);
+        });
+
+        query.after = Some(radicle_git_ext::Oid::try_from(
+            "0000000000000000000000000000000000000001",
+        )?);
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        query.limit = None;
+        query.after = None;
+        query.path = Some("text/arrows.txt".to_string());
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have["headers"][0]["sha1"],
+                "1e0206da8571ca71c51c91154e2fee376e09b4e7"
+            );
+            assert_eq!(have["next"], Value::Null);
+        });
+
+        Ok(())
+    }
+
+    #[allow(clippy::indexing_slicing)]
+    #[tokio::test]
+    async fn commits_paginated_across_merge() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+
+        let mut query = super::CommitsQuery {
+            revision: Some(radicle_source::Revision::Branch {
+                name: "master".to_string(),
+                peer_id: None,
+            }),
+            limit: None,
+            after: None,
+            author: None,
+            since: None,
+            until: None,
+            path: None,
+        };
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let all = request().method("GET").path(&path).reply(&api).await;
+        let all: Value = serde_json::from_slice(all.body())?;
+        let all = all["headers"].as_array().unwrap().clone();
+        assert_eq!(all.len(), 15);
+
+        // End the first page on the merge, so both of its parents are left for the second one.
+        let merge = all
+            .iter()
+            .position(|header| header["sha1"].as_str().unwrap().starts_with("223aaf8"))
+            .unwrap();
+        query.limit = Some(merge + 1);
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let first: Value =
+            serde_json::from_slice(request().method("GET").path(&path).reply(&api).await.body())?;
+        assert_eq!(first["next"], all[merge]["sha1"]);
+
+        query.limit = None;
+        query.after = Some(serde_json::from_value(first["next"].clone())?);
+        let path = format!("/commits/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let second: Value =
+            serde_json::from_slice(request().method("GET").path(&path).reply(&api).await.body())?;
+        assert_eq!(second["next"], Value::Null);
+
+        let paged = first["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(second["headers"].as_array().unwrap())
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(paged, all);
+
+        Ok(())
+    }
+
+    #[allow(clippy::indexing_slicing)]
+    #[tokio::test]
+    async fn compare() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+
+        let base = radicle_source::Revision::Branch {
+            name: "master".to_string(),
+            peer_id: None,
+        };
+        let head = radicle_source::Revision::Branch {
+            name: "dev".to_string(),
+            peer_id: None,
+        };
+        let query = super::CompareQuery { base, head };
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/compare/{}?{}",
+                urn,
+                serde_qs::to_string(&query).unwrap()
+            ))
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert!(have["base"].as_str().unwrap().starts_with("a0dd912"));
+            assert!(have["head"].as_str().unwrap().starts_with("27acd68"));
+            assert!(have["commits"][0]["sha1"]
+                .as_str()
+                .unwrap()
+                .starts_with("27acd68"));
+            assert_eq!(
+                have["commits"][0]["summary"],
+                json!("Commit on the dev branch")
+            );
+            // Commits only on the base branch are left out.
+            let summaries = have["commits"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|commit| commit["summary"].clone())
+                .collect::<Vec<_>>();
+            assert!(!summaries.contains(&json!("Merge pull request #4 from FintanH/fintan")));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[allow(clippy::indexing_slicing)]
+    async fn graph() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let query = super::GraphQuery {
+            peer_ids: Some(vec![ctx.peer.peer_id()]),
+            limit: None,
+        };
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/graph/{}?{}",
+                urn,
+                serde_qs::to_string(&query).unwrap()
+            ))
+            .reply(&api)
+            .await;
+
+        let tips = source::graph::tips(&ctx.peer, &urn, Some(&[None]))?;
+        let want = source::graph::graph(&ctx.peer, tips, None)?;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, json!(want));
+            assert_eq!(have["truncated"], json!(false));
+
+            let commits = have["commits"].as_array().unwrap();
+            let tip = |name: &str| {
+                commits
+                    .iter()
+                    .find(|commit| commit["branches"] == json!([{ "peerId": null, "name": name }]))
+                    .unwrap()
+            };
+            let master = tip("master");
+            assert!(master["sha1"].as_str().unwrap().starts_with("a0dd912"));
+            assert_eq!(
+                master["summary"],
+                json!("Add files with special characters in their filenames (#5)")
+            );
+            let dev = tip("dev");
+            assert!(dev["sha1"].as_str().unwrap().starts_with("27acd68"));
+            assert_eq!(dev["summary"], json!("Commit on the dev branch"));
+
+            let merge = commits
+                .iter()
+                .find(|commit| commit["sha1"].as_str().unwrap().starts_with("223aaf8"))
+                .unwrap();
+            assert_eq!(
+                merge["summary"],
+                json!("Merge pull request #4 from FintanH/fintan")
+            );
+            assert_eq!(merge["parents"].as_array().unwrap().len(), 2);
+            assert_eq!(merge["branches"], json!([]));
+        });
+
+        let query = super::GraphQuery {
+            peer_ids: Some(vec![ctx.peer.peer_id()]),
+            limit: Some(1),
+        };
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/graph/{}?{}",
+                urn,
+                serde_qs::to_string(&query).unwrap()
+            ))
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["truncated"], json!(true));
+            assert_eq!(have["commits"].as_array().unwrap().len(), 1);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn history() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let revision = radicle_source::Revision::Branch {
+            name: "master".to_string(),
+            peer_id: None,
+        };
+        let query = super::HistoryQuery {
+            path: "text/arrows.txt".to_string(),
+            peer_id: None,
+            revision: Some(revision),
+        };
+        let path = format!("/history/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have,
+                json!([{
+                    "header": {
+                        "sha1": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                        "author": {
+                            "name": "Rūdolfs Ošiņš",
+                            "email": "rudolfs@osins.org",
+                        },
+                        "committer": {
+                            "name": "Rūdolfs Ošiņš",
+                            "email": "rudolfs@osins.org",
+                        },
+                        "summary": "Add text files",
+                        "description": "",
+                        "committerTime": 1_575_283_425,
+                    },
+                    "path": "text/arrows.txt",
+                    "previousPath": null,
+                }])
+            );
+        });
+
+        let query = super::HistoryQuery {
+            path: "does/not/exist".to_string(),
+            peer_id: None,
+            revision: None,
+        };
+        let path = format!("/history/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn local_state() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let mut path = env::current_dir()?;
+        path.push("../../fixtures/git-platinum");
+
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/local-state?{}",
+                serde_qs::to_string(&super::LocalStateQuery {
+                    path: path.to_str().unwrap().to_string()
+                })
+                .unwrap()
+            ))
+            .reply(&api)
+            .await;
+
+        let want = source::working_copy::status(&ctx.peer, &path)?;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, json!(want));
+            assert_eq!(have["branches"], json!(["dev", "master"]));
+            assert_eq!(have["rad"], Value::Null);
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn local_state_changes() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let path = tmp_dir.path().join("working-copy");
+        let repo = git2::Repository::init_opts(
+            &path,
+            git2::RepositoryInitOptions::new().initial_head("master"),
+        )?;
+        std::fs::write(path.join("README.md"), "# Hello")?;
+        std::fs::write(path.join("LICENSE"), "MIT")?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new("README.md"))?;
+        index.add_path(std::path::Path::new("LICENSE"))?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])?;
+
+        std::fs::write(path.join("README.md"), "# Hello, world")?;
+        std::fs::remove_file(path.join("LICENSE"))?;
+        std::fs::write(path.join("notes.txt"), "todo")?;
+
+        let res = request()
+            .method("GET")
+            .path(&format!(
+                "/local-state?{}",
+                serde_qs::to_string(&super::LocalStateQuery {
+                    path: path.to_str().unwrap().to_string()
+                })
+                .unwrap()
+            ))
+            .reply(&api)
+            .await;
+
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have,
+                json!({
+                    "branches": ["master"],
+                    "head": "master",
+                    "dirty": true,
+                    "changes": [
+                        { "path": "LICENSE", "kind": "deleted" },
+                        { "path": "README.md", "kind": "modified" },
+                    ],
+                    "untracked": ["notes.txt"],
+                    "rad": null,
+                })
+            );
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let urn = replicate_platinum(&ctx).await?;
+        let query = super::RawQuery {
+            path: "text/arrows.txt".to_string(),
+            peer_id: None,
+            revision: None,
+        };
+        let path = format!("/raw/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+        assert_eq!(res.headers()["accept-ranges"], "bytes");
+        let content = res.body().clone();
+        assert!(content.starts_with(b"  ;;;;;"));
+
+        let res = request()
+            .method("GET")
+            .path(&path)
+            .header("range", "bytes=2-6")
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers()["content-range"],
+            format!("bytes 2-6/{}", content.len()).as_str()
+        );
+        assert_eq!(res.body().as_ref(), b";;;;;");
+
+        let res = request()
+            .method("GET")
+            .path(&path)
+            .header("range", format!("bytes={}-", content.len()))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        let query = super::RawQuery {
+            path: "bin/ls".to_string(),
+            peer_id: None,
+            revision: None,
+        };
+        let path = format!("/raw/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/octet-stream");
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn branches() -> Result<(), Box<dyn std::error::Error>> {
+    async fn readme() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
         let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
         let api = super::filters(ctx.clone().into());
+
         let urn = replicate_platinum(&ctx).await?;
 
         let res = request()
             .method("GET")
-            .path(&format!("/branches/{}", urn))
+            .path(&format!("/readme/{}", urn))
             .reply(&api)
             .await;
-
-        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
-        let want = browser::using(&ctx.peer, default_branch, |browser| {
-            radicle_source::branches(browser, RefScope::All)
-        })?;
-
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
-            assert_eq!(have, json!(["dev", "master"]));
+            assert_eq!(have["path"], json!("README.md"));
+            assert!(!have["html"].as_str().unwrap().is_empty());
         });
 
+        let query = super::ReadmeQuery {
+            prefix: Some("src".to_string()),
+            peer_id: None,
+            revision: None,
+        };
+        let path = format!("/readme/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
         Ok(())
     }
 
     #[tokio::test]
-    #[allow(clippy::indexing_slicing)]
-    async fn commit() -> Result<(), Box<dyn std::error::Error>> {
+    async fn resolve() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
         let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
         let api = super::filters(ctx.clone().into());
-
         let urn = replicate_platinum(&ctx).await?;
-        let sha1 = radicle_git_ext::Oid::try_from("3873745c8f6ffb45c990eb23b491d4b4b6182f95")?;
+
+        let path = |rev: &str| {
+            let query = super::ResolveQuery {
+                rev: rev.to_string(),
+            };
+            format!("/resolve/{}?{}", urn, serde_qs::to_string(&query).unwrap())
+        };
+
+        let default_branch =
+            radicle_daemon::state::find_default_branch(&ctx.peer, urn.clone()).await?;
+        let master = browser::using(&ctx.peer, default_branch, |browser| {
+            browser::resolve(
+                browser,
+                Some(radicle_source::Revision::Branch {
+                    name: "master".to_string(),
+                    peer_id: None,
+                }),
+            )
+        })?;
 
         let res = request()
             .method("GET")
-            .path(&format!("/commit/{}/{}", urn, sha1))
+            .path(&path("master"))
             .reply(&api)
             .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(
+                have,
+                json!({
+                    "type": "resolved",
+                    "sha1": master.to_string(),
+                    "peerId": null,
+                    "kind": "branch",
+                    "name": "master",
+                })
+            );
+        });
 
-        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
-        let want = browser::using(&ctx.peer, default_branch, |mut browser| {
-            radicle_source::commit::header(&mut browser, *sha1)
-        })?;
+        let res = request()
+            .method("GET")
+            .path(&path(&format!("{}/master", ctx.peer.peer_id())))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["sha1"], json!(master.to_string()));
+        });
 
+        // Abbreviated commit ids are completed.
+        let res = request()
+            .method("GET")
+            .path(&path("1e0206d"))
+            .reply(&api)
+            .await;
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have["header"], json!(want));
             assert_eq!(
-                have["header"],
+                have,
                 json!({
-                    "sha1": sha1,
-                    "author": {
-                        "name": "Fintan Halpenny",
-                        "email": "fintan.halpenny@gmail.com",
-                    },
-                    "committer": {
-                        "email": "noreply@github.com",
-                        "name": "GitHub",
-                    },
-                    "summary": "Extend the docs (#2)",
-                    "description": "I want to have files under src that have separate commits.\r\nThat way src\'s latest commit isn\'t the same as all its files, instead it\'s the file that was touched last.",
-                    "committerTime": 1_578_309_972,
-                }),
+                    "type": "resolved",
+                    "sha1": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                    "peerId": null,
+                    "kind": "commit",
+                    "name": null,
+                })
             );
         });
 
+        let res = request()
+            .method("GET")
+            .path(&path("not-a-branch"))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
         Ok(())
     }
 
     #[tokio::test]
-    async fn commits() -> Result<(), Box<dyn std::error::Error>> {
+    async fn search() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
         let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
         let api = super::filters(ctx.clone().into());
 
         let urn = replicate_platinum(&ctx).await?;
-
-        let branch_name = "dev";
-        let revision = radicle_source::Revision::Branch {
-            name: branch_name.to_string(),
+        let query = super::SearchQuery {
+            q: "':`".to_string(),
+            regex: None,
+            case_sensitive: None,
+            path: Some("text".to_string()),
+            limit: None,
             peer_id: None,
+            revision: None,
         };
-        let query = super::CommitsQuery {
-            revision: Some(revision.clone()),
-        };
-        let res = request()
-            .method("GET")
-            .path(&format!(
-                "/commits/{}?{}",
-                urn.clone(),
-                serde_qs::to_string(&query).unwrap(),
-            ))
-            .reply(&api)
-            .await;
-
-        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
-        let want = browser::using(&ctx.peer, default_branch, |mut browser| {
-            radicle_source::commits(&mut browser, Some(revision.clone()))
-        })?;
+        let path = format!("/search/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["truncated"], json!(false));
+            let arrows = have["files"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|file| file["path"] == "text/arrows.txt")
+                .expect("no match in text/arrows.txt");
+            assert_eq!(
+                arrows["matches"],
+                json!([{
+                    "lineNumber": 7,
+                    "line": "   ':`          ':`          ':`",
+                    "ranges": [
+                        { "start": 3, "end": 6 },
+                        { "start": 16, "end": 19 },
+                        { "start": 29, "end": 32 },
+                    ],
+                    "before": [
+                        "..;;;;;..    ..;;;;;..    ..;;;;;..",
+                        " ':::::'      ':::::'      ':::::'",
+                    ],
+                    "after": [],
+                }])
+            );
+        });
 
+        let query = super::SearchQuery {
+            q: "there is no such line in platinum".to_string(),
+            ..query
+        };
+        let path = format!("/search/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
+            assert_eq!(have["files"], json!([]));
+            assert_eq!(have["truncated"], json!(false));
         });
 
+        let query = super::SearchQuery {
+            q: "(".to_string(),
+            regex: Some(true),
+            ..query
+        };
+        let path = format!("/search/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
         Ok(())
     }
 
     #[tokio::test]
-    async fn local_state() -> Result<(), Box<dyn std::error::Error>> {
+    async fn tag() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir()?;
         let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
         let api = super::filters(ctx.clone().into());
 
-        let mut path = env::current_dir()?;
-        path.push("../../fixtures/git-platinum");
+        let urn = replicate_platinum(&ctx).await?;
+        let target = git2::Oid::from_str("1e0206da8571ca71c51c91154e2fee376e09b4e7")?;
+        let reference = format!("{}tags/release", source::refs_prefix(&urn, None));
+        browser::using_repository(&ctx.peer, |repo| {
+            let tagger = git2::Signature::new(
+                "cloudhead",
+                "cloudhead@radicle.xyz",
+                &git2::Time::new(1_600_000_000, 0),
+            )?;
+            let tag = repo.tag_annotation_create(
+                "release",
+                &repo.find_object(target, None)?,
+                &tagger,
+                "First release\n",
+            )?;
+            repo.reference(&reference, tag, false, "tag release")?;
+            Ok(())
+        })?;
 
         let res = request()
             .method("GET")
-            .path(&format!(
-                "/local-state?{}",
-                serde_qs::to_string(&super::LocalStateQuery {
-                    path: path.to_str().unwrap().to_string()
-                })
-                .unwrap()
-            ))
+            .path(&format!("/tag/{}/release", urn))
             .reply(&api)
             .await;
-
-        let want = radicle_source::local_state(path.to_str().unwrap(), "master").unwrap();
-
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
             assert_eq!(
                 have,
                 json!({
-                    "branches": [
-                        "dev",
-                        "master",
-                    ],
-                }),
+                    "name": "release",
+                    "target": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                    "annotation": {
+                        "tagger": {
+                            "name": "cloudhead",
+                            "email": "cloudhead@radicle.xyz",
+                            "time": 1_600_000_000,
+                        },
+                        "message": "First release\n",
+                    },
+                    "commit": {
+                        "sha1": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                        "author": {
+                            "name": "Rūdolfs Ošiņš",
+                            "email": "rudolfs@osins.org",
+                        },
+                        "committer": {
+                            "name": "Rūdolfs Ošiņš",
+                            "email": "rudolfs@osins.org",
+                        },
+                        "summary": "Add text files",
+                        "description": "",
+                        "committerTime": 1_575_283_425,
+                    },
+                })
             );
         });
 
+        let res = request()
+            .method("GET")
+            .path(&format!("/tag/{}/v9.9.9", urn))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
         Ok(())
     }
 
@@ -667,18 +2688,81 @@ mod test {
             .reply(&api)
             .await;
 
-        let default_branch = radicle_daemon::state::find_default_branch(&ctx.peer, urn).await?;
-        let want = browser::using(&ctx.peer, default_branch, |browser| {
-            radicle_source::tags(browser)
-        })?;
         http::test::assert_response(&res, StatusCode::OK, |have| {
-            assert_eq!(have, json!(want));
+            let names = have
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tag| tag["name"].clone())
+                .collect::<Vec<_>>();
             assert_eq!(
-                have,
-                json!(["v0.1.0", "v0.2.0", "v0.3.0", "v0.4.0", "v0.5.0", "v0.6.0"]),
+                names,
+                vec!["v0.1.0", "v0.2.0", "v0.3.0", "v0.4.0", "v0.5.0", "v0.6.0"],
             );
         });
 
+        // Peers that are not tracked have no tags.
+        let remote = link_crypto::PeerId::from(link_crypto::SecretKey::new());
+        let res = request()
+            .method("GET")
+            .path(&format!("/tags/{}?peerId={}", urn, remote))
+            .reply(&api)
+            .await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, json!([]));
+        });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn themes() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+
+        let res = request().method("GET").path("/themes").reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have, json!(ctx.themes.list()));
+            assert!(have.as_array().unwrap().contains(
+                &json!({ "name": "base16-ocean.dark", "origin": "builtin", "dark": true })
+            ));
+        });
+
+        let urn = replicate_platinum(&ctx).await?;
+        let query = super::BlobQuery {
+            path: "src/memory.rs".to_string(),
+            peer_id: None,
+            revision: None,
+            highlight: Some("InspiredGitHub".to_string()),
+            start_line: None,
+            end_line: None,
+            max_bytes: None,
+        };
+        let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["html"], json!(true));
+        });
+
+        let query = super::BlobQuery {
+            highlight: Some("h4x0r".to_string()),
+            ..query
+        };
+        let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            assert_eq!(have["html"], json!(true));
+        });
+
+        let query = super::BlobQuery {
+            highlight: Some("no-such-theme".to_string()),
+            ..query
+        };
+        let path = format!("/blob/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+        let res = request().method("GET").path(&path).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
         Ok(())
     }
 
@@ -785,6 +2869,217 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn tree_not_modified() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+        let urn = replicate_platinum(&ctx).await?;
+
+        let tree = |revision: radicle_source::Revision<link_crypto::PeerId>| {
+            let query = super::TreeQuery {
+                prefix: Some("text".to_string()),
+                peer_id: None,
+                revision: Some(revision),
+            };
+            format!("/tree/{}?{}", urn, serde_qs::to_string(&query).unwrap())
+        };
+        let branch = tree(radicle_source::Revision::Branch {
+            name: "master".to_string(),
+            peer_id: None,
+        });
+        let sha = tree(radicle_source::Revision::Sha {
+            sha: git2::Oid::from_str("1e0206da8571ca71c51c91154e2fee376e09b4e7")?.into(),
+        });
+
+        let res = request().method("GET").path(&branch).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["cache-control"], "private, no-cache");
+        let etag = res.headers()["etag"].clone();
+
+        let res = request()
+            .method("GET")
+            .path(&branch)
+            .header("if-none-match", etag.clone())
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()["etag"], etag);
+        assert!(res.body().is_empty());
+
+        // Commits never change, so trees at a sha may be cached for good.
+        let res = request().method("GET").path(&sha).reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()["cache-control"],
+            "private, max-age=31536000, immutable"
+        );
+        assert_ne!(res.headers()["etag"], etag);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_modified() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+        let urn = replicate_platinum(&ctx).await?;
+
+        let dev = radicle_source::Revision::Branch {
+            name: "dev".to_string(),
+            peer_id: None,
+        };
+        let commits = super::CommitsQuery {
+            revision: Some(dev.clone()),
+            limit: Some(2),
+            after: None,
+            author: None,
+            since: None,
+            until: None,
+            path: None,
+        };
+        let compare = super::CompareQuery {
+            base: radicle_source::Revision::Branch {
+                name: "master".to_string(),
+                peer_id: None,
+            },
+            head: dev,
+        };
+        let paths = vec![
+            format!("/branches/{}", urn),
+            format!("/commit/{}/1e0206da8571ca71c51c91154e2fee376e09b4e7", urn),
+            format!("/commits/{}?{}", urn, serde_qs::to_string(&commits)?),
+            format!("/compare/{}?{}", urn, serde_qs::to_string(&compare)?),
+            format!("/graph/{}", urn),
+            format!("/tag/{}/v0.1.0", urn),
+            format!("/tags/{}", urn),
+        ];
+
+        for path in paths {
+            let res = request().method("GET").path(&path).reply(&api).await;
+            assert_eq!(res.status(), StatusCode::OK, "{}", path);
+            assert_eq!(
+                res.headers()["cache-control"],
+                "private, no-cache",
+                "{}",
+                path
+            );
+            let etag = res.headers()["etag"].clone();
+
+            let res = request()
+                .method("GET")
+                .path(&path)
+                .header("if-none-match", etag.clone())
+                .reply(&api)
+                .await;
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED, "{}", path);
+            assert_eq!(res.headers()["etag"], etag, "{}", path);
+            assert!(res.body().is_empty(), "{}", path);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[allow(clippy::indexing_slicing)]
+    async fn tree_with_submodule_not_modified() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let (ctx, _) = context::Unsealed::tmp(&tmp_dir)?;
+        let api = super::filters(ctx.clone().into());
+        let urn = replicate_platinum(&ctx).await?;
+
+        // Pin a submodule at the root of a commit on top of master.
+        let prefix = source::refs_prefix(&urn, None);
+        let commit = browser::using_repository(&ctx.peer, |repo| {
+            let master = repo
+                .find_reference(&format!("{}heads/master", prefix))?
+                .peel_to_commit()?;
+            let modules = repo.blob(
+                b"[submodule \"lib\"]\n\tpath = lib\n\turl = https://example.com/lib.git\n",
+            )?;
+            let mut builder = repo.treebuilder(Some(&master.tree()?))?;
+            builder.insert(".gitmodules", modules, 0o100_644)?;
+            builder.insert(
+                "lib",
+                git2::Oid::from_str("1e0206da8571ca71c51c91154e2fee376e09b4e7")?,
+                0o160_000,
+            )?;
+            let tree = repo.find_tree(builder.write()?)?;
+            let signature = git2::Signature::new(
+                "cloudhead",
+                "cloudhead@radicle.xyz",
+                &git2::Time::new(1_600_000_000, 0),
+            )?;
+            let commit = repo.commit(
+                Some(&format!("{}heads/submodule", prefix)),
+                &signature,
+                &signature,
+                "Add a submodule",
+                &tree,
+                &[&master],
+            )?;
+            Ok(commit)
+        })?;
+
+        let query = super::TreeQuery {
+            prefix: None,
+            peer_id: None,
+            revision: Some(radicle_source::Revision::Sha { sha: commit.into() }),
+        };
+        let path = format!("/tree/{}?{}", urn, serde_qs::to_string(&query).unwrap());
+
+        let res = request().method("GET").path(&path).reply(&api).await;
+        // The submodule may link to a project later, so the tree must be revalidated.
+        assert_eq!(res.headers()["cache-control"], "private, no-cache");
+        let etag = res.headers()["etag"].clone();
+        http::test::assert_response(&res, StatusCode::OK, |have| {
+            let lib = have["entries"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|entry| entry["path"] == json!("lib"))
+                .unwrap();
+            assert_eq!(lib["info"]["objectType"], json!("SUBMODULE"));
+            assert_eq!(
+                lib["info"]["submodule"],
+                json!({
+                    "commit": "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+                    "url": "https://example.com/lib.git",
+                    "project": null,
+                })
+            );
+        });
+
+        let res = request()
+            .method("GET")
+            .path(&path)
+            .header("if-none-match", etag.clone())
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()["etag"], etag);
+
+        Ok(())
+    }
+
+    /// Strip the window of a text blob response to compare it with [`radicle_source::blob`].
+    fn without_window(blob: &Value) -> Value {
+        let mut blob = blob.clone();
+        if let Some(fields) = blob.as_object_mut() {
+            for field in &[
+                "startLine",
+                "endLine",
+                "totalBytes",
+                "totalLines",
+                "truncated",
+            ] {
+                fields.remove(*field);
+            }
+        }
+        blob
+    }
+
     async fn replicate_platinum(ctx: &context::Unsealed) -> Result<Urn, error::Error> {
         let owner = radicle_daemon::state::init_owner(
             &ctx.peer,
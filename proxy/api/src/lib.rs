@@ -35,6 +35,7 @@
 )]
 
 mod browser;
+mod cache;
 mod config;
 mod context;
 mod control;
@@ -55,5 +56,6 @@ mod process;
 mod project;
 mod service;
 mod session;
+mod source;
 
 pub use process::{run, Args};
@@ -4,24 +4,40 @@
 // with Radicle Linking Exception. For full terms see the included
 // LICENSE file.
 
-//! [`list`] all the [`Patch`]es for project.
+//! [`list`] all the [`Patch`]es for project, [`get`] the [`Detail`]s of one, [`create`] a new
+//! one, [`update`] or [`close`] it, and compare two of its [`Revision`]s with [`range_diff`].
+
+use std::collections::{HashMap, HashSet};
 
 use either::Either;
 use radicle_git_ext::Oid;
-use radicle_source::surf::git::RefScope;
-use serde::Serialize;
+use radicle_source::{
+    commit::Header,
+    surf::{diff::Diff, git::RefScope, vcs::git::git2},
+};
+use serde::{Deserialize, Serialize};
 
 use link_crypto::{BoxedSigner, PeerId};
 use link_identities::git::Urn;
 
-use crate::project;
+use crate::{browser, project, source};
+
+pub mod comment;
 
 const TAG_PREFIX: &str = "radicle-patch/";
 
+/// Prefix of the tags an author marks their patches as closed with.
+const CLOSED_TAG_PREFIX: &str = "radicle-patch-closed/";
+
+/// Prefix of the tags that record the revisions of a patch, followed by `<id>/<number>`.
+const REVISION_TAG_PREFIX: &str = "radicle-patch-revision/";
+
 /// A patch is a change set that a user wants the maintainer to merge into a projects default
 /// branch.
 ///
-/// A patch is represented by an annotated tag, prefixed with `radicle-patch/`.
+/// A patch is represented by an annotated tag, prefixed with `radicle-patch/`. The author closes
+/// it by publishing a tag with the same id, prefixed with `radicle-patch-closed/`. Every version
+/// of the patch is kept as a [`Revision`].
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Patch {
@@ -36,6 +52,78 @@ pub struct Patch {
     /// The merge base of [`Patch::commit`] and the head commit of the first maintainer's default
     /// branch.
     pub merge_base: Option<Oid>,
+    /// Whether the patch still awaits a decision.
+    pub status: Status,
+    /// The versions of the patch, oldest first. The last one is the current version.
+    pub revisions: Vec<Revision>,
+}
+
+/// A version of a [`Patch`], recorded by an annotated tag prefixed with
+/// `radicle-patch-revision/<id>/` and followed by the revision number.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    /// Number of the revision, starting at 1.
+    pub number: usize,
+    /// Head commit of the patch in this revision.
+    pub commit: Oid,
+    /// Message of the patch in this revision.
+    pub message: Option<String>,
+}
+
+/// The changes between two [`Revision`]s of a [`Patch`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeDiff {
+    /// The older revision.
+    pub from: Revision,
+    /// The newer revision.
+    pub to: Revision,
+    /// The commits of both revisions, paired up.
+    pub commits: Vec<source::range_diff::Pair>,
+}
+
+/// State of a [`Patch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    /// Neither merged nor closed.
+    Open,
+    /// [`Patch::commit`] is an ancestor of the first maintainer's default branch.
+    Merged,
+    /// The author marked the patch as closed.
+    Closed,
+}
+
+impl Status {
+    /// The status of a patch with the head `commit` and `merge_base`, `closed` if its author
+    /// published a closing tag for it. A merged patch counts as merged even if it was closed.
+    fn of(commit: Oid, merge_base: Option<Oid>, closed: bool) -> Self {
+        if merge_base == Some(commit) {
+            Self::Merged
+        } else if closed {
+            Self::Closed
+        } else {
+            Self::Open
+        }
+    }
+}
+
+/// A [`Patch`] with the changes it introduces.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Detail {
+    /// The patch itself.
+    #[serde(flatten)]
+    pub patch: Patch,
+    /// The commits between [`Patch::merge_base`] and [`Patch::commit`], oldest first.
+    pub commits: Vec<Header>,
+    /// Combined changes of all [`Detail::commits`].
+    pub diff: Diff,
+    /// Line statistics of [`Detail::diff`].
+    pub stats: source::DiffStats,
+    /// Line statistics of every file changed by the patch.
+    pub files: Vec<source::FileStats>,
 }
 
 /// List all patches for the given project.
@@ -49,31 +137,7 @@ pub async fn list(
 ) -> Result<Vec<Patch>, crate::error::Error> {
     let mut patches = Vec::new();
 
-    let default_branch_head_commit_id = {
-        let project = radicle_daemon::state::get_project(peer, project_urn.clone())
-            .await?
-            .ok_or_else(|| radicle_daemon::state::Error::ProjectNotFound(project_urn.clone()))?;
-        let maintainer = project
-            .delegations()
-            .iter()
-            .flat_map(|either| match either {
-                Either::Left(pk) => Either::Left(std::iter::once(pk)),
-                Either::Right(indirect) => Either::Right(indirect.delegations().iter()),
-            })
-            .next()
-            .expect("missing delegation");
-        let default_branch = radicle_daemon::state::get_branch(
-            peer,
-            project_urn.clone(),
-            Some(PeerId::from(*maintainer)),
-            None,
-        )
-        .await?;
-        crate::browser::using(peer, default_branch, move |browser| {
-            Ok(browser.get().first().clone())
-        })?
-        .id
-    };
+    let default_branch_head_commit_id = default_branch_head(peer, project_urn.clone()).await?;
 
     for project_peer in radicle_daemon::state::list_project_peers(peer, project_urn.clone()).await?
     {
@@ -109,6 +173,36 @@ pub async fn list(
             let patches = &mut patches;
             move |browser| {
                 let tags = browser.list_tags(ref_scope)?;
+                let closed = tags
+                    .iter()
+                    .filter_map(|tag| {
+                        let name = match tag {
+                            radicle_source::surf::git::Tag::Light { name, .. }
+                            | radicle_source::surf::git::Tag::Annotated { name, .. } => name,
+                        };
+                        name.to_string()
+                            .strip_prefix(CLOSED_TAG_PREFIX)
+                            .map(ToString::to_string)
+                    })
+                    .collect::<HashSet<_>>();
+                let mut recorded = HashMap::<String, Vec<Revision>>::new();
+                for tag in &tags {
+                    if let radicle_source::surf::git::Tag::Annotated {
+                        target_id,
+                        name,
+                        message,
+                        ..
+                    } = tag
+                    {
+                        if let Some((id, number)) = parse_revision(&name.to_string()) {
+                            recorded.entry(id).or_default().push(Revision {
+                                number,
+                                commit: Oid::from(*target_id),
+                                message: message.clone(),
+                            });
+                        }
+                    }
+                }
                 for tag in tags {
                     match tag {
                         radicle_source::surf::git::Tag::Light { .. } => {
@@ -125,15 +219,24 @@ pub async fn list(
                                 None => continue,
                             };
 
+                            let commit = Oid::from(target_id);
                             let merge_base = browser
                                 .merge_base(target_id, default_branch_head_commit_id)?
                                 .map(Oid::from);
+                            let status = Status::of(commit, merge_base, closed.contains(&id));
+                            let revisions = revisions(
+                                recorded.remove(&id).unwrap_or_default(),
+                                commit,
+                                &message,
+                            );
                             patches.push(Patch {
                                 id,
                                 peer: project_peer.clone().into(),
                                 message,
-                                commit: Oid::from(target_id),
+                                commit,
                                 merge_base,
+                                status,
+                                revisions,
                             });
                         },
                     }
@@ -146,3 +249,586 @@ pub async fn list(
 
     Ok(patches)
 }
+
+/// Get the patch `id` published by `peer_id`, with the commits and changes it introduces over the
+/// default branch of the first maintainer.
+///
+/// # Errors
+/// * Cannot access the monorepo
+/// * There is no patch `id` of `peer_id`
+/// * The history or the changes of the patch could not be read
+pub async fn get(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    peer_id: PeerId,
+    id: &str,
+) -> Result<Detail, crate::error::Error> {
+    let remote = Some(peer_id).filter(|peer_id| *peer_id != peer.peer_id());
+    let patch = find(peer, project_urn.clone(), peer_id, id).await?;
+
+    let base = default_branch_head(peer, project_urn.clone()).await?;
+    let reference = radicle_daemon::state::get_branch(peer, project_urn, remote, None).await?;
+    let comparison = source::compare::compare(
+        peer,
+        reference,
+        Some(radicle_source::Revision::Sha { sha: base.into() }),
+        Some(radicle_source::Revision::Sha {
+            sha: patch.commit.into(),
+        }),
+    )?;
+
+    let from = comparison.merge_base.unwrap_or(comparison.base);
+    let files = browser::using_repository(peer, |repo| {
+        source::file_stats(
+            repo,
+            git2::Oid::from(from),
+            git2::Oid::from(comparison.head),
+        )
+    })?;
+    let mut commits = comparison.commits;
+    commits.reverse();
+
+    Ok(Detail {
+        patch,
+        commits,
+        diff: comparison.diff,
+        stats: comparison.stats,
+        files,
+    })
+}
+
+/// Create the patch `id` of the local peer for the `revision` in its namespace, `title` and
+/// `description` make up the message of the patch. If no `id` is given it is derived from the
+/// `title`.
+///
+/// The annotated tag is written to the namespace of the project in the monorepo, together with
+/// the tag of its first [`Revision`], and the refs of the local peer are signed, which lets the
+/// daemon announce the patch to the network.
+///
+/// # Errors
+/// * The `title` is empty or `id` is not a valid tag name
+/// * The local peer already published a patch `id`
+/// * The `revision` could not be found in the namespace of the local peer
+/// * Cannot access the monorepo
+pub async fn create(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    id: Option<String>,
+    title: &str,
+    description: &str,
+) -> Result<Detail, crate::error::Error> {
+    let message = message(title, description)?;
+    let id = id.unwrap_or_else(|| slug(title));
+    let name = format!("{}{}", TAG_PREFIX, id);
+    let reference = tag_reference(&project_urn, &name);
+    if id.is_empty() || !git2::Reference::is_valid_name(&reference) {
+        return Err(crate::error::Error::InvalidPatchId(id));
+    }
+
+    let handle = handle(peer).await?;
+    let peer_id = peer.peer_id();
+    let commit = resolve(peer, project_urn.clone(), revision).await?;
+
+    browser::using_repository(peer, |repo| {
+        if repo.find_reference(&reference).is_ok() {
+            return Err(crate::error::Error::PatchExists(id.clone()));
+        }
+
+        let target = repo.find_object(commit, Some(git2::ObjectType::Commit))?;
+        let tag =
+            repo.tag_annotation_create(&name, &target, &tagger(&handle, peer_id)?, &message)?;
+        repo.reference(&reference, tag, false, &format!("create patch {}", id))?;
+        repo.reference(
+            &tag_reference(&project_urn, &revision_name(&id, 1)),
+            tag,
+            false,
+            &format!("create revision 1 of patch {}", id),
+        )?;
+
+        Ok(())
+    })?;
+
+    sign_refs(peer, project_urn.clone()).await?;
+
+    get(peer, project_urn, peer.peer_id(), &id).await
+}
+
+/// Publish a new [`Revision`] of the patch `id` of the local peer for the `revision` in its
+/// namespace. The commit, `title` and `description` of the patch are kept unless new ones are
+/// given, if none of them change no revision is published.
+///
+/// The previous version stays available as a [`Revision`] and the refs of the local peer are
+/// signed, which lets the daemon announce the update to the network.
+///
+/// # Errors
+/// * The local peer has no patch `id`
+/// * The `title` is empty
+/// * The `revision` could not be found in the namespace of the local peer
+/// * Cannot access the monorepo
+pub async fn update(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    id: &str,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    title: Option<&str>,
+    description: Option<&str>,
+) -> Result<Detail, crate::error::Error> {
+    let patch = find(peer, project_urn.clone(), peer.peer_id(), id).await?;
+
+    let current = patch.message.clone().unwrap_or_default();
+    let mut lines = current.splitn(2, '\n');
+    let current_title = lines.next().unwrap_or_default();
+    let current_description = lines.next().unwrap_or_default();
+    let message = message(
+        title.unwrap_or(current_title),
+        description.unwrap_or(current_description),
+    )?;
+
+    let commit = match revision {
+        Some(revision) => resolve(peer, project_urn.clone(), Some(revision)).await?,
+        None => patch.commit.into(),
+    };
+    if Oid::from(commit) == patch.commit && patch.message.as_ref() == Some(&message) {
+        return get(peer, project_urn, peer.peer_id(), id).await;
+    }
+
+    let handle = handle(peer).await?;
+    let peer_id = peer.peer_id();
+
+    let name = format!("{}{}", TAG_PREFIX, id);
+    let reference = tag_reference(&project_urn, &name);
+    let number = patch.revisions.last().map_or(1, |revision| revision.number);
+    browser::using_repository(peer, |repo| {
+        // Patches updated by hand may not have recorded their current version yet.
+        let current = repo
+            .find_reference(&reference)?
+            .target()
+            .ok_or_else(|| crate::error::Error::PatchNotFound(id.to_string()))?;
+        let current_revision = tag_reference(&project_urn, &revision_name(id, number));
+        if repo.find_reference(&current_revision).is_err() {
+            repo.reference(
+                &current_revision,
+                current,
+                false,
+                &format!("create revision {} of patch {}", number, id),
+            )?;
+        }
+
+        let target = repo.find_object(commit, Some(git2::ObjectType::Commit))?;
+        let tag =
+            repo.tag_annotation_create(&name, &target, &tagger(&handle, peer_id)?, &message)?;
+        repo.reference(
+            &tag_reference(&project_urn, &revision_name(id, number + 1)),
+            tag,
+            false,
+            &format!("create revision {} of patch {}", number + 1, id),
+        )?;
+        repo.reference(&reference, tag, true, &format!("update patch {}", id))?;
+
+        Ok(())
+    })?;
+
+    sign_refs(peer, project_urn.clone()).await?;
+
+    get(peer, project_urn, peer.peer_id(), id).await
+}
+
+/// Close the patch `id` of the local peer by tagging its current commit with the `id`, prefixed
+/// with `radicle-patch-closed/`. Closing a closed patch leaves it as it is.
+///
+/// The refs of the local peer are signed, which lets the daemon announce the closing to the
+/// network.
+///
+/// # Errors
+/// * The local peer has no patch `id`
+/// * Cannot access the monorepo
+pub async fn close(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    id: &str,
+) -> Result<(), crate::error::Error> {
+    let patch = find(peer, project_urn.clone(), peer.peer_id(), id).await?;
+    let handle = handle(peer).await?;
+    let peer_id = peer.peer_id();
+
+    let name = format!("{}{}", CLOSED_TAG_PREFIX, id);
+    let reference = tag_reference(&project_urn, &name);
+    let closed = browser::using_repository(peer, |repo| {
+        if repo.find_reference(&reference).is_ok() {
+            return Ok(false);
+        }
+
+        let target = repo.find_object(patch.commit.into(), Some(git2::ObjectType::Commit))?;
+        let tag = repo.tag_annotation_create(
+            &name,
+            &target,
+            &tagger(&handle, peer_id)?,
+            &format!("Close patch {}", id),
+        )?;
+        repo.reference(&reference, tag, false, &format!("close patch {}", id))?;
+
+        Ok(true)
+    })?;
+
+    if closed {
+        sign_refs(peer, project_urn).await?;
+    }
+
+    Ok(())
+}
+
+/// Compare the revisions `from` and `to` of the patch `id` published by `peer_id`. Without `to`
+/// the last revision is used, without `from` the one before `to`. If `to` is the first revision
+/// and no `from` is given, there is nothing to compare and no commits are returned.
+///
+/// The commits of both revisions are those reachable from the revision but not from the default
+/// branch of the first maintainer, see [`source::range_diff::range_diff`].
+///
+/// # Errors
+/// * There is no patch `id` of `peer_id` or it has no such revisions
+/// * The history or the changes of the revisions could not be read
+pub async fn range_diff(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    peer_id: PeerId,
+    id: &str,
+    from: Option<usize>,
+    to: Option<usize>,
+) -> Result<RangeDiff, crate::error::Error> {
+    let patch = find(peer, project_urn.clone(), peer_id, id).await?;
+    let last = patch.revisions.last().map_or(1, |revision| revision.number);
+    let revision = |number: usize| {
+        patch
+            .revisions
+            .iter()
+            .find(|revision| revision.number == number)
+            .cloned()
+            .ok_or_else(|| crate::error::Error::PatchRevisionNotFound {
+                id: id.to_string(),
+                revision: number,
+            })
+    };
+    let to = revision(to.unwrap_or(last))?;
+    let from = match from {
+        Some(from) => revision(from)?,
+        None if to.number <= 1 => {
+            return Ok(RangeDiff {
+                from: to.clone(),
+                to,
+                commits: Vec::new(),
+            })
+        },
+        None => revision(to.number - 1)?,
+    };
+
+    let base = default_branch_head(peer, project_urn).await?;
+    let commits = browser::using_repository(peer, |repo| {
+        let old = source::range_diff::series(repo, base, from.commit.into())?;
+        let new = source::range_diff::series(repo, base, to.commit.into())?;
+
+        source::range_diff::range_diff(repo, &old, &new)
+    })?;
+
+    Ok(RangeDiff { from, to, commits })
+}
+
+/// Find the patch `id` published by `peer_id` by reading its tags in the namespace of the peer.
+async fn find(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    peer_id: PeerId,
+    id: &str,
+) -> Result<Patch, crate::error::Error> {
+    let remote = Some(peer_id).filter(|peer_id| *peer_id != peer.peer_id());
+    let project_peer = radicle_daemon::state::list_project_peers(peer, project_urn.clone())
+        .await?
+        .into_iter()
+        .find(|project_peer| match project_peer {
+            radicle_daemon::project::Peer::Local { .. } => remote.is_none(),
+            radicle_daemon::project::Peer::Remote { peer_id, .. } => remote == Some(*peer_id),
+        })
+        .ok_or_else(|| crate::error::Error::PatchNotFound(id.to_string()))?;
+    let base = default_branch_head(peer, project_urn.clone()).await?;
+
+    let tags = format!("{}tags/", source::refs_prefix(&project_urn, remote));
+    browser::using_repository(peer, |repo| {
+        let tag = repo
+            .find_reference(&format!("{}{}{}", tags, TAG_PREFIX, id))
+            .and_then(|reference| reference.peel(git2::ObjectType::Tag))
+            .ok()
+            .and_then(|object| object.into_tag().ok())
+            .ok_or_else(|| crate::error::Error::PatchNotFound(id.to_string()))?;
+        let commit = Oid::from(tag.target_id());
+        let message = tag.message().map(ToString::to_string);
+        let closed = repo
+            .find_reference(&format!("{}{}{}", tags, CLOSED_TAG_PREFIX, id))
+            .is_ok();
+        let merge_base = match repo.merge_base(tag.target_id(), base) {
+            Ok(merge_base) => Some(Oid::from(merge_base)),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut recorded = Vec::new();
+        for reference in
+            repo.references_glob(&format!("{}{}{}/*", tags, REVISION_TAG_PREFIX, id))?
+        {
+            let reference = reference?;
+            // The glob also matches the revisions of patches whose id continues with a slash.
+            let number = match reference
+                .name()
+                .and_then(|name| name.strip_prefix(&tags))
+                .and_then(parse_revision)
+            {
+                Some((revision_id, number)) if revision_id == id => number,
+                _ => continue,
+            };
+            let revision = match reference.peel(git2::ObjectType::Tag) {
+                Ok(object) => object,
+                Err(_) => continue,
+            };
+            if let Some(revision) = revision.as_tag() {
+                recorded.push(Revision {
+                    number,
+                    commit: revision.target_id().into(),
+                    message: revision.message().map(ToString::to_string),
+                });
+            }
+        }
+
+        Ok(Patch {
+            id: id.to_string(),
+            peer: project_peer.into(),
+            status: Status::of(commit, merge_base, closed),
+            revisions: revisions(recorded, commit, &message),
+            message,
+            commit,
+            merge_base,
+        })
+    })
+}
+
+/// Resolve `revision` in the namespace of the local peer, its default branch if `None`.
+async fn resolve(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    revision: Option<radicle_source::Revision<PeerId>>,
+) -> Result<git2::Oid, crate::error::Error> {
+    let branch = radicle_daemon::state::get_branch(peer, project_urn, None, None).await?;
+
+    browser::using(peer, branch, move |browser| {
+        browser::resolve(browser, revision)
+    })
+}
+
+/// The handle of the default owner, who patches are tagged by, see [`tagger`].
+async fn handle(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+) -> Result<String, crate::error::Error> {
+    let owner = radicle_daemon::state::default_owner(peer)
+        .await?
+        .ok_or(radicle_daemon::state::Error::MissingOwner)?
+        .into_inner()
+        .into_inner();
+
+    Ok(owner.subject().name.to_string())
+}
+
+/// The signature to tag patches of the owner with `handle` and `peer_id` with.
+fn tagger(handle: &str, peer_id: PeerId) -> Result<git2::Signature<'static>, git2::Error> {
+    git2::Signature::now(handle, &format!("{}@{}", handle, peer_id))
+}
+
+/// Sign the refs of the local peer in the namespace of the project, so that they are announced.
+async fn sign_refs(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+) -> Result<(), crate::error::Error> {
+    peer.using_storage(move |storage| librad::git::refs::Refs::update(storage, &project_urn))
+        .await
+        .map_err(radicle_daemon::state::Error::from)?
+        .map_err(radicle_daemon::state::Error::from)?;
+
+    Ok(())
+}
+
+/// The message of a patch with `title` and `description`.
+fn message(title: &str, description: &str) -> Result<String, crate::error::Error> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(crate::error::Error::EmptyPatchTitle);
+    }
+
+    let description = description.trim();
+    Ok(if description.is_empty() {
+        title.to_string()
+    } else {
+        format!("{}\n\n{}", title, description)
+    })
+}
+
+/// The name of the reference of the local tag `name` in the namespace of the project.
+fn tag_reference(project_urn: &Urn, name: &str) -> String {
+    format!("{}tags/{}", source::refs_prefix(project_urn, None), name)
+}
+
+/// The name of the tag recording the revision `number` of the patch `id`.
+fn revision_name(id: &str, number: usize) -> String {
+    format!("{}{}/{}", REVISION_TAG_PREFIX, id, number)
+}
+
+/// Parse the name of a tag recording a [`Revision`] into the id of its patch and its number.
+fn parse_revision(name: &str) -> Option<(String, usize)> {
+    let (id, number) = name.strip_prefix(REVISION_TAG_PREFIX)?.rsplit_once('/')?;
+
+    Some((id.to_string(), number.parse().ok()?))
+}
+
+/// The `recorded` revisions of a patch in order, followed by its current version if it was
+/// updated without recording a revision.
+fn revisions(mut recorded: Vec<Revision>, commit: Oid, message: &Option<String>) -> Vec<Revision> {
+    recorded.sort_by_key(|revision| revision.number);
+    match recorded.last() {
+        Some(last) if last.commit == commit && &last.message == message => {},
+        last => {
+            let number = last.map_or(1, |last| last.number + 1);
+            recorded.push(Revision {
+                number,
+                commit,
+                message: message.clone(),
+            });
+        },
+    }
+
+    recorded
+}
+
+/// Derive a patch id from the `title` by keeping its lower case alphanumeric characters, joined
+/// by single dashes.
+fn slug(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Get the head commit of the default branch of the first maintainer of the project.
+async fn default_branch_head(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+) -> Result<git2::Oid, crate::error::Error> {
+    let project = radicle_daemon::state::get_project(peer, project_urn.clone())
+        .await?
+        .ok_or_else(|| radicle_daemon::state::Error::ProjectNotFound(project_urn.clone()))?;
+    let maintainer = project
+        .delegations()
+        .iter()
+        .flat_map(|either| match either {
+            Either::Left(pk) => Either::Left(std::iter::once(pk)),
+            Either::Right(indirect) => Either::Right(indirect.delegations().iter()),
+        })
+        .next()
+        .expect("missing delegation");
+    let default_branch = radicle_daemon::state::get_branch(
+        peer,
+        project_urn.clone(),
+        Some(PeerId::from(*maintainer)),
+        None,
+    )
+    .await?;
+
+    Ok(browser::using(peer, default_branch, move |browser| {
+        Ok(browser.get().first().clone())
+    })?
+    .id)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use radicle_git_ext::Oid;
+    use radicle_source::surf::vcs::git::git2;
+
+    use super::{Revision, Status};
+
+    #[test]
+    fn status() -> Result<(), git2::Error> {
+        let commit = Oid::from(git2::Oid::from_str(
+            "80ded66281a4de2889cc07293a8f10947c6d57fe",
+        )?);
+        let base = Oid::from(git2::Oid::from_str(
+            "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+        )?);
+
+        assert_eq!(Status::of(commit, Some(base), false), Status::Open);
+        assert_eq!(Status::of(commit, None, false), Status::Open);
+        assert_eq!(Status::of(commit, Some(commit), false), Status::Merged);
+        assert_eq!(Status::of(commit, Some(base), true), Status::Closed);
+        assert_eq!(Status::of(commit, Some(commit), true), Status::Merged);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_revision() {
+        assert_eq!(
+            super::parse_revision("radicle-patch-revision/fix/build/2"),
+            Some(("fix/build".to_string(), 2))
+        );
+        assert_eq!(super::parse_revision("radicle-patch-revision/fix"), None);
+        assert_eq!(super::parse_revision("radicle-patch-revision/fix/x"), None);
+        assert_eq!(super::parse_revision("radicle-patch/fix/1"), None);
+    }
+
+    #[test]
+    fn revisions() -> Result<(), git2::Error> {
+        let first = Oid::from(git2::Oid::from_str(
+            "80ded66281a4de2889cc07293a8f10947c6d57fe",
+        )?);
+        let second = Oid::from(git2::Oid::from_str(
+            "1e0206da8571ca71c51c91154e2fee376e09b4e7",
+        )?);
+        let revision = |number, commit| Revision {
+            number,
+            commit,
+            message: Some("Fix the build".to_string()),
+        };
+        let message = Some("Fix the build".to_string());
+
+        // Patches created by hand have no recorded revisions.
+        assert_eq!(
+            super::revisions(vec![], first, &message),
+            vec![revision(1, first)]
+        );
+        assert_eq!(
+            super::revisions(
+                vec![revision(2, second), revision(1, first)],
+                second,
+                &message
+            ),
+            vec![revision(1, first), revision(2, second)]
+        );
+        // The tag was moved without recording a revision.
+        assert_eq!(
+            super::revisions(vec![revision(1, first)], second, &message),
+            vec![revision(1, first), revision(2, second)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn slug() {
+        assert_eq!(super::slug("Fix the build"), "fix-the-build");
+        assert_eq!(
+            super::slug("  Add `patch::create` (API)! "),
+            "add-patch-create-api"
+        );
+        assert_eq!(super::slug("Ünïcode 42"), "ünïcode-42");
+        assert_eq!(super::slug("?!"), "");
+    }
+}
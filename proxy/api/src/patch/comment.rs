@@ -0,0 +1,329 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`list`] the discussion of a [`super::Patch`] or [`post`] a [`Comment`] to it.
+//!
+//! The comments of a peer on a patch are a chain of commits with empty trees, one per comment,
+//! under `refs/radicle-patch-comments/<author>/<id>` in the peer's namespace of the project,
+//! where `<author>` is the peer that published the patch. They get a ref category of their own
+//! because `refs/notes/` is read by `git notes`, which expects notes trees, and `refs/rad/` holds
+//! the identity refs that are verified on replication. Like every category in the namespace of a
+//! peer they are covered by its signed refs and replicate with the project.
+//!
+//! The author of a comment is the peer whose namespace holds it. The commit signature is only
+//! set by the author and not verified, so it is not exposed.
+
+use std::collections::{HashMap, HashSet};
+
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+
+use crate::{browser, error::Error, source};
+
+/// Prefix of the references holding the comments on patches, relative to the `refs/` of a peer.
+const REF_PREFIX: &str = "radicle-patch-comments/";
+
+/// Trailer of the message of a comment that replies to another one.
+const REPLY_TO_TRAILER: &str = "Reply-To: ";
+
+/// A comment on a patch, with the replies to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// Id of the commit holding the comment.
+    pub id: Oid,
+    /// Peer that posted the comment, the one whose namespace holds it.
+    pub peer_id: PeerId,
+    /// Time the comment was posted, in seconds since the epoch.
+    pub timestamp: i64,
+    /// Text of the comment.
+    pub body: String,
+    /// The comment this is a reply to, `None` if it starts a thread.
+    pub reply_to: Option<Oid>,
+    /// Replies to the comment, oldest first.
+    pub replies: Vec<Comment>,
+}
+
+/// List the discussion of the patch `id` published by `patch_peer_id`, by all peers of the
+/// project. Comments that start a thread come first, oldest first, with their replies nested.
+///
+/// # Errors
+/// * There is no patch `id` of `patch_peer_id`
+/// * Cannot access the monorepo
+pub async fn list(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    patch_peer_id: PeerId,
+    id: &str,
+) -> Result<Vec<Comment>, Error> {
+    let comments = comments(peer, &project_urn, patch_peer_id, id).await?;
+    super::find(peer, project_urn, patch_peer_id, id).await?;
+
+    Ok(thread(comments))
+}
+
+/// Post a comment on the patch `id` published by `patch_peer_id` in the namespace of the local
+/// peer, as a reply to the comment `reply_to` if given. The refs of the local peer are signed,
+/// which lets the daemon announce the comment to the network.
+///
+/// # Errors
+/// * The `body` is empty
+/// * There is no patch `id` of `patch_peer_id` or no comment `reply_to` on it
+/// * Cannot access the monorepo
+pub async fn post(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: Urn,
+    patch_peer_id: PeerId,
+    id: &str,
+    body: &str,
+    reply_to: Option<Oid>,
+) -> Result<Comment, Error> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(Error::EmptyComment);
+    }
+
+    super::find(peer, project_urn.clone(), patch_peer_id, id).await?;
+    if let Some(reply_to) = reply_to {
+        let comments = comments(peer, &project_urn, patch_peer_id, id).await?;
+        if !comments.iter().any(|comment| comment.id == reply_to) {
+            return Err(Error::CommentNotFound(reply_to.to_string()));
+        }
+    }
+
+    let handle = super::handle(peer).await?;
+    let peer_id = peer.peer_id();
+    let reference = format!(
+        "{}{}",
+        source::refs_prefix(&project_urn, None),
+        reference(patch_peer_id, id)
+    );
+    let comment = browser::using_repository(peer, |repo| {
+        let signature = super::tagger(&handle, peer_id)?;
+        let parent = match repo.find_reference(&reference) {
+            Ok(reference) => Some(reference.peel_to_commit()?),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+        let tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
+        let commit = repo.commit(
+            Some(&reference),
+            &signature,
+            &signature,
+            &message(body, reply_to),
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )?;
+
+        Ok(comment(&repo.find_commit(commit)?, peer_id))
+    })?;
+
+    super::sign_refs(peer, project_urn).await?;
+
+    Ok(comment)
+}
+
+/// All comments of all peers on the patch `id` published by `patch_peer_id`, without replies.
+async fn comments(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    project_urn: &Urn,
+    patch_peer_id: PeerId,
+    id: &str,
+) -> Result<Vec<Comment>, Error> {
+    let local = peer.peer_id();
+    let mut peer_ids = vec![local];
+    for project_peer in radicle_daemon::state::list_project_peers(peer, project_urn.clone()).await?
+    {
+        if let radicle_daemon::project::Peer::Remote { peer_id, .. } = project_peer {
+            peer_ids.push(peer_id);
+        }
+    }
+
+    browser::using_repository(peer, |repo| {
+        let mut comments = Vec::new();
+        for peer_id in peer_ids {
+            let remote = Some(peer_id).filter(|remote| *remote != local);
+            let reference = format!(
+                "{}{}",
+                source::refs_prefix(project_urn, remote),
+                reference(patch_peer_id, id)
+            );
+            let tip = match repo.refname_to_id(&reference) {
+                Ok(tip) => tip,
+                Err(err) if err.code() == git2::ErrorCode::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut revwalk = repo.revwalk()?;
+            revwalk.simplify_first_parent()?;
+            revwalk.push(tip)?;
+            for commit in revwalk {
+                comments.push(comment(&repo.find_commit(commit?)?, peer_id));
+            }
+        }
+
+        Ok(comments)
+    })
+}
+
+/// The reference of the comments on the patch `id` of `patch_peer_id`, relative to the `refs/`
+/// of a peer.
+fn reference(patch_peer_id: PeerId, id: &str) -> String {
+    format!("{}{}/{}", REF_PREFIX, patch_peer_id, id)
+}
+
+/// Read the comment of `peer_id` held by `commit`.
+fn comment(commit: &git2::Commit, peer_id: PeerId) -> Comment {
+    let (body, reply_to) = parse_message(commit.message().unwrap_or_default());
+
+    Comment {
+        id: Oid::from(commit.id()),
+        peer_id,
+        timestamp: commit.time().seconds(),
+        body,
+        reply_to,
+        replies: Vec::new(),
+    }
+}
+
+/// The message of the commit holding a comment with `body`.
+fn message(body: &str, reply_to: Option<Oid>) -> String {
+    match reply_to {
+        Some(reply_to) => format!("{}\n\n{}{}", body, REPLY_TO_TRAILER, reply_to),
+        None => body.to_string(),
+    }
+}
+
+/// Parse the message of the commit holding a comment into its body and the comment it replies
+/// to.
+fn parse_message(message: &str) -> (String, Option<Oid>) {
+    let message = message.trim_end();
+    let (rest, last_line) = match message.rfind('\n') {
+        Some(index) => message.split_at(index),
+        None => ("", message),
+    };
+    let reply_to = last_line
+        .trim()
+        .strip_prefix(REPLY_TO_TRAILER)
+        .and_then(|oid| git2::Oid::from_str(oid).ok());
+
+    match reply_to {
+        Some(reply_to) => (rest.trim_end().to_string(), Some(Oid::from(reply_to))),
+        None => (message.to_string(), None),
+    }
+}
+
+/// Nest the replies among `comments` into the comments they reply to. Replies to unknown
+/// comments start a thread of their own.
+fn thread(mut comments: Vec<Comment>) -> Vec<Comment> {
+    comments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+
+    let ids = comments
+        .iter()
+        .map(|comment| comment.id)
+        .collect::<HashSet<_>>();
+    let mut replies = HashMap::<Oid, Vec<Comment>>::new();
+    let mut roots = Vec::new();
+    for comment in comments {
+        match comment.reply_to.filter(|reply_to| ids.contains(reply_to)) {
+            Some(reply_to) => replies.entry(reply_to).or_default().push(comment),
+            None => roots.push(comment),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|root| nest(root, &mut replies))
+        .collect()
+}
+
+/// Attach the `replies` to `comment` and recursively to its replies.
+fn nest(mut comment: Comment, replies: &mut HashMap<Oid, Vec<Comment>>) -> Comment {
+    comment.replies = replies
+        .remove(&comment.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|reply| nest(reply, replies))
+        .collect();
+
+    comment
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use radicle_git_ext::Oid;
+    use radicle_source::surf::vcs::git::git2;
+
+    use link_crypto::{PeerId, SecretKey};
+
+    use super::Comment;
+
+    fn oid(hex: &str) -> Oid {
+        Oid::from(git2::Oid::from_str(hex).expect("invalid oid"))
+    }
+
+    #[test]
+    fn parse_message() {
+        let reply_to = oid("80ded66281a4de2889cc07293a8f10947c6d57fe");
+
+        assert_eq!(
+            super::parse_message("Looks good.\n\nNit: rename `x`.\n"),
+            ("Looks good.\n\nNit: rename `x`.".to_string(), None)
+        );
+        assert_eq!(
+            super::parse_message(&super::message("Agreed.\n\nDone.", Some(reply_to))),
+            ("Agreed.\n\nDone.".to_string(), Some(reply_to))
+        );
+        assert_eq!(
+            super::parse_message("Reply-To: nobody"),
+            ("Reply-To: nobody".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn thread() {
+        let peer_id = PeerId::from(SecretKey::new());
+        let comment = |id: &str, timestamp: i64, reply_to: Option<&str>| Comment {
+            id: oid(id),
+            peer_id,
+            timestamp,
+            body: String::new(),
+            reply_to: reply_to.map(oid),
+            replies: Vec::new(),
+        };
+        let first = "1e0206da8571ca71c51c91154e2fee376e09b4e7";
+        let second = "80ded66281a4de2889cc07293a8f10947c6d57fe";
+        let reply = "91b69e00cd8e5a07e20942e9e4457d83ce7a3ff1";
+        let nested = "27acd68c7504755aa11023300890bb85bbd69d45";
+        let orphan = "223aaf87d6ea62eef0014857640fd7c8dd0f80b5";
+
+        let threads = super::thread(vec![
+            comment(nested, 4, Some(reply)),
+            comment(second, 2, None),
+            comment(reply, 3, Some(first)),
+            comment(first, 1, None),
+            comment(orphan, 5, Some("0000000000000000000000000000000000000001")),
+        ]);
+
+        let mut want_reply = comment(reply, 3, Some(first));
+        want_reply.replies = vec![comment(nested, 4, Some(reply))];
+        let mut want_first = comment(first, 1, None);
+        want_first.replies = vec![want_reply];
+        assert_eq!(
+            threads,
+            vec![
+                want_first,
+                comment(second, 2, None),
+                comment(orphan, 5, Some("0000000000000000000000000000000000000001")),
+            ]
+        );
+    }
+}
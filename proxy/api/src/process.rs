@@ -15,7 +15,7 @@ use futures::prelude::*;
 use thiserror::Error;
 use tokio::sync::{watch, RwLock};
 
-use crate::{config, context, git_helper, http, notification, service, session};
+use crate::{config, context, git_helper, http, notification, service, session, source};
 
 /// Flags accepted by the proxy binary.
 #[derive(Clone, FromArgs)]
@@ -297,6 +297,12 @@ async fn rig(
 
     let store = kv::Store::new(kv::Config::new(store_path).flush_every_ms(100))?;
 
+    let themes_path = if let Some(temp_dir) = &environment.temp_dir {
+        temp_dir.path().join("themes")
+    } else {
+        config::themes_dir()
+    };
+
     if let Some(key) = environment.key.clone() {
         let signer = link_crypto::BoxedSigner::new(link_crypto::SomeSigner { signer: key });
 
@@ -329,6 +335,7 @@ async fn rig(
             auth_token,
             keystore: environment.keystore.clone(),
             shutdown: Arc::new(tokio::sync::Notify::new()),
+            themes: Arc::new(source::theme::Registry::load(&themes_path)),
         });
 
         Ok(Rigging {
@@ -9,13 +9,20 @@
 
 use std::{collections::HashSet, convert::TryFrom, ops::Deref};
 
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
+use librad::git::types::{Reference, Single};
 use link_crypto::BoxedSigner;
 use link_identities::{git::Urn, Person, Project as LinkProject};
-use radicle_source::surf::vcs::git::Stats;
+use radicle_source::surf::vcs::git::{self, RefScope};
 
-use crate::{browser, error, identity};
+use crate::{browser, cache::Cache, error, identity, source};
+
+lazy_static! {
+    /// Totals by the head commit and the number of branches they were computed for.
+    static ref TOTALS: Cache<(git::Oid, usize), git::Stats> = Cache::new(1024);
+}
 
 /// Object encapsulating project metadata.
 #[derive(Deserialize, Serialize)]
@@ -77,6 +84,44 @@ pub struct Project<S> {
     pub stats: S,
 }
 
+/// Statistics of the default branch of a project.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// Number of branches, commits and contributors.
+    #[serde(flatten)]
+    pub totals: git::Stats,
+    /// Size and number of files per language at the head of the branch.
+    pub languages: Vec<source::languages::Language>,
+}
+
+/// Compute the [`Stats`] for `branch`. The results are memoised by the head commit of the branch.
+///
+/// # Errors
+///
+///   * Failed to get the totals of the branch.
+///   * Failed to walk the tree at the head of the branch.
+pub fn stats(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    branch: Reference<Single>,
+) -> Result<Stats, error::Error> {
+    let scope = RefScope::from(branch.remote);
+    let (head, totals) = browser::using(peer, branch, |browser| {
+        let head = browser::resolve(browser, None)?;
+        let branches = radicle_source::branches(browser, scope)?.len();
+        let totals = TOTALS.get_or_try_insert_with((head, branches), || {
+            browser.get_stats().map_err(radicle_source::Error::from)
+        })?;
+        Ok((head, totals))
+    })?;
+    let languages = browser::using_repository(peer, |repo| {
+        let tree = repo.find_commit(head)?.tree()?;
+        source::languages::breakdown(repo, &tree)
+    })?;
+
+    Ok(Stats { totals, languages })
+}
+
 /// A `Partial` project is one where we _weren't_ able to fetch the [`Stats`] for it.
 pub type Partial = Project<()>;
 
@@ -243,9 +288,7 @@ impl Projects {
                 Ok(branch) => branch,
             };
 
-            let stats = match browser::using(peer, default_branch, |browser| {
-                Ok(browser.get_stats()?)
-            }) {
+            let stats = match stats(peer, default_branch) {
                 Err(err) => {
                     tracing::warn!(project_urn = %project.urn, ?err, "cannot get project stats");
                     projects.failures.push(Failure::Stats(project));
@@ -352,7 +395,7 @@ pub async fn get(
         .ok_or(crate::error::Error::ProjectNotFound)?;
 
     let branch = radicle_daemon::state::find_default_branch(peer, project_urn.clone()).await?;
-    let project_stats = browser::using(peer, branch, |browser| Ok(browser.get_stats()?))?;
+    let project_stats = stats(peer, branch)?;
 
     Full::try_from((project, project_stats))
 }
@@ -393,7 +436,7 @@ pub async fn list_for_user(
                 subject.default_branch.clone(),
             )
             .await?;
-            let stats = browser::using(peer, branch, |browser| Ok(browser.get_stats()?))?;
+            let stats = stats(peer, branch)?;
             let full = Full::try_from((project, stats))?;
 
             projects.push(full);
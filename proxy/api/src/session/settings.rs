@@ -27,6 +27,10 @@ pub struct Appearance {
     pub ui_font: UIFont,
     /// Currently active code font.
     pub code_font: CodeFont,
+    /// Syntax highlighting theme for code, follows [`Appearance::theme`] if `None`. See
+    /// [`crate::source::theme::Registry::list`] for the available themes.
+    #[serde(default)]
+    pub code_theme: Option<String>,
     /// User dismissable hints.
     pub hints: Hints,
 }
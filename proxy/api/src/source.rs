@@ -0,0 +1,191 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Source browsing that goes beyond the single revision views provided by [`radicle_source`].
+
+use std::convert::TryFrom as _;
+
+use lazy_static::lazy_static;
+use radicle_source::{
+    commit::Header,
+    surf::{
+        diff::{Diff, FileDiff, LineDiff},
+        vcs::git::{self, git2},
+    },
+};
+use serde::Serialize;
+use syntect::parsing::SyntaxSet;
+
+use link_crypto::PeerId;
+use link_identities::git::Urn;
+
+use crate::{browser, error::Error};
+
+pub mod activity;
+pub mod archive;
+pub mod blame;
+pub mod commit;
+pub mod compare;
+pub mod graph;
+pub mod history;
+pub mod languages;
+pub mod range_diff;
+pub mod raw;
+pub mod readme;
+pub mod resolve;
+pub mod search;
+pub mod tag;
+pub mod theme;
+pub mod tree;
+pub mod window;
+pub mod working_copy;
+
+lazy_static! {
+    /// Syntax definitions used for highlighting and to detect the language of blobs.
+    static ref SYNTAXES: SyntaxSet = SyntaxSet::load_defaults_newlines();
+}
+
+/// Aggregated line statistics of a [`Diff`].
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStats {
+    /// Number of files touched.
+    pub files: usize,
+    /// Number of lines added.
+    pub additions: u64,
+    /// Number of lines deleted.
+    pub deletions: u64,
+}
+
+impl From<&Diff> for DiffStats {
+    fn from(diff: &Diff) -> Self {
+        let mut stats = Self {
+            files: diff.created.len()
+                + diff.deleted.len()
+                + diff.modified.len()
+                + diff.moved.len()
+                + diff.copied.len(),
+            ..Self::default()
+        };
+
+        let file_diffs = diff
+            .created
+            .iter()
+            .map(|file| &file.diff)
+            .chain(diff.deleted.iter().map(|file| &file.diff))
+            .chain(diff.modified.iter().map(|file| &file.diff));
+        for file_diff in file_diffs {
+            let (additions, deletions) = count_lines(file_diff);
+            stats.additions += additions;
+            stats.deletions += deletions;
+        }
+
+        stats
+    }
+}
+
+/// Line statistics of a single file changed between two commits.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStats {
+    /// Path of the file after the change.
+    pub path: String,
+    /// Path of the file before the change if it was renamed.
+    pub previous_path: Option<String>,
+    /// Number of lines added.
+    pub additions: usize,
+    /// Number of lines deleted.
+    pub deletions: usize,
+}
+
+/// Compute the [`FileStats`] of every file changed between the commits `from` and `to`, sorted by
+/// path. Renames are detected.
+///
+/// # Errors
+///   * If either commit or the diff between them could not be read.
+pub fn file_stats(
+    repo: &git2::Repository,
+    from: git2::Oid,
+    to: git2::Oid,
+) -> Result<Vec<FileStats>, Error> {
+    let from = repo.find_commit(from)?.tree()?;
+    let to = repo.find_commit(to)?.tree()?;
+    let mut diff = repo.diff_tree_to_tree(Some(&from), Some(&to), None)?;
+    diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+
+    let path = |file: git2::DiffFile| file.path().map(|path| path.display().to_string());
+    let mut stats = Vec::with_capacity(diff.deltas().len());
+    for (index, delta) in diff.deltas().enumerate() {
+        let (additions, deletions) = match git2::Patch::from_diff(&diff, index)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions, deletions)
+            },
+            None => (0, 0),
+        };
+        let new_path = path(delta.new_file());
+        let old_path = path(delta.old_file());
+        let previous_path = if delta.status() == git2::Delta::Renamed {
+            old_path.clone()
+        } else {
+            None
+        };
+
+        stats.push(FileStats {
+            path: new_path.or(old_path).unwrap_or_default(),
+            previous_path,
+            additions,
+            deletions,
+        });
+    }
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(stats)
+}
+
+/// Count the added and deleted lines of a single file diff.
+fn count_lines(diff: &FileDiff) -> (u64, u64) {
+    let mut additions = 0;
+    let mut deletions = 0;
+
+    if let FileDiff::Plain { hunks } = diff {
+        for hunk in hunks.iter() {
+            for line in &hunk.lines {
+                match line {
+                    LineDiff::Addition { .. } => additions += 1,
+                    LineDiff::Deletion { .. } => deletions += 1,
+                    LineDiff::Context { .. } => {},
+                }
+            }
+        }
+    }
+
+    (additions, deletions)
+}
+
+/// Build the [`Header`] of a raw `commit` in the same shape [`radicle_source`] serialises it.
+///
+/// # Errors
+///   * If the commit could not be read, e.g. because its author is not valid UTF-8.
+pub fn header(commit: git2::Commit) -> Result<Header, Error> {
+    let commit = git::Commit::try_from(commit).map_err(radicle_source::Error::from)?;
+
+    Ok(Header::from(&commit))
+}
+
+/// Prefix of the references of `peer_id` in the namespace of the project `urn` within the
+/// monorepo. `None` stands for the local peer.
+#[must_use]
+pub fn refs_prefix(urn: &Urn, peer_id: Option<PeerId>) -> String {
+    match peer_id {
+        None => format!("refs/namespaces/{}/refs/", urn.encode_id()),
+        Some(peer_id) => format!(
+            "refs/namespaces/{}/refs/remotes/{}/",
+            urn.encode_id(),
+            peer_id
+        ),
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Count the commits of every author per week over the history of a revision, see [`activity`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use crate::{browser, error::Error, project, source};
+
+/// Length of the buckets commits are counted in, in seconds.
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+/// The Unix epoch was a Thursday, weeks are shifted by this offset to start on Mondays.
+const MONDAY_OFFSET: i64 = 3 * 24 * 60 * 60;
+
+/// Commits per author and week over the history of a revision.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    /// The commit the history was walked from.
+    pub head: Oid,
+    /// All authors of the history, the most active first.
+    pub authors: Vec<Author>,
+}
+
+/// An author of commits, identified by their email address.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    /// Name of the author as given in their most recent commit.
+    pub name: String,
+    /// Email address of the author.
+    pub email: String,
+    /// The peer the author is known as, if one could be determined.
+    pub peer: Option<project::Peer>,
+    /// Number of commits in the whole history.
+    pub commits: usize,
+    /// Weeks in which the author committed, oldest first. Weeks without commits are left out.
+    pub weeks: Vec<Week>,
+}
+
+/// The commits of an author in a week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Week {
+    /// Start of the week, Monday 00:00 UTC, in seconds since the epoch.
+    pub start: i64,
+    /// Number of commits authored in the week.
+    pub commits: usize,
+}
+
+/// A commit of the history with the parts of its author relevant to the activity.
+struct Commit {
+    /// Id of the commit.
+    oid: git2::Oid,
+    /// Name of the author.
+    name: String,
+    /// Email address of the author.
+    email: String,
+    /// Time the commit was authored, in seconds since the epoch.
+    time: i64,
+}
+
+/// The commits of an author collected while walking the history.
+struct Tally {
+    /// Name of the author as given in their most recent commit.
+    name: String,
+    /// Email address of the author.
+    email: String,
+    /// Number of commits by the start of their week.
+    weeks: BTreeMap<i64, usize>,
+    /// Ids of the commits.
+    oids: Vec<git2::Oid>,
+}
+
+/// Count the commits per author and week over the history of `revision`, looked up in the
+/// namespace of `reference`.
+///
+/// Authors are mapped to one of the `peers` of the project `urn` by the branches of the peers
+/// that contain their commits. If the branches of only one peer contain commits the others don't
+/// have, or only one peer has any of the commits at all, the author is mapped to that peer.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If the history or the branches of the peers could not be walked.
+pub fn activity(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    urn: &Urn,
+    peers: Vec<project::Peer>,
+) -> Result<Activity, Error> {
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        let commits = history(repo, head)?;
+        let oids = commits
+            .iter()
+            .map(|commit| commit.oid)
+            .collect::<HashSet<_>>();
+
+        let mut reachable = Vec::with_capacity(peers.len());
+        for project_peer in &peers {
+            let remote = match &**project_peer {
+                radicle_daemon::project::Peer::Local { .. } => None,
+                radicle_daemon::project::Peer::Remote { peer_id, .. } => Some(*peer_id),
+            };
+            reachable.push(branches_reachable(repo, urn, remote, &oids)?);
+        }
+
+        let mut tallies = HashMap::<String, Tally>::new();
+        // The history is walked newest first, so the first commit of an author names them.
+        for commit in commits {
+            let tally = tallies
+                .entry(commit.email.to_lowercase())
+                .or_insert_with(|| Tally {
+                    name: commit.name.clone(),
+                    email: commit.email.clone(),
+                    weeks: BTreeMap::new(),
+                    oids: Vec::new(),
+                });
+            *tally.weeks.entry(week_start(commit.time)).or_default() += 1;
+            tally.oids.push(commit.oid);
+        }
+
+        let mut authors = tallies
+            .into_values()
+            .map(|tally| Author {
+                peer: attribute(&tally.oids, &reachable).map(|index| peers[index].clone()),
+                name: tally.name,
+                email: tally.email,
+                commits: tally.oids.len(),
+                weeks: tally
+                    .weeks
+                    .into_iter()
+                    .map(|(start, commits)| Week { start, commits })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        authors.sort_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.email.cmp(&b.email))
+        });
+
+        Ok(Activity {
+            head: head.into(),
+            authors,
+        })
+    })
+}
+
+/// Walk the history of `head`, newest first.
+fn history(repo: &git2::Repository, head: git2::Oid) -> Result<Vec<Commit>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push(head)?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author();
+        commits.push(Commit {
+            oid: commit.id(),
+            name: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            time: author.when().seconds(),
+        });
+    }
+
+    Ok(commits)
+}
+
+/// Collect the commits out of `oids` reachable from the branches `remote` published in the
+/// namespace of `urn`, or the local peer if `remote` is `None`.
+fn branches_reachable(
+    repo: &git2::Repository,
+    urn: &Urn,
+    remote: Option<PeerId>,
+    oids: &HashSet<git2::Oid>,
+) -> Result<HashSet<git2::Oid>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    let glob = format!("{}heads/*", source::refs_prefix(urn, remote));
+    for reference in repo.references_glob(&glob)? {
+        if let Ok(tip) = reference?.peel_to_commit() {
+            revwalk.push(tip.id())?;
+        }
+    }
+
+    revwalk
+        .filter(|oid| oid.as_ref().map_or(true, |oid| oids.contains(oid)))
+        .collect::<Result<_, _>>()
+        .map_err(Error::from)
+}
+
+/// Find the index of the peer an author of the commits `oids` is mapped to, given the commits
+/// `reachable` from the branches of every peer.
+fn attribute(oids: &[git2::Oid], reachable: &[HashSet<git2::Oid>]) -> Option<usize> {
+    let containing = |oid: &git2::Oid| {
+        reachable
+            .iter()
+            .enumerate()
+            .filter(|(_, commits)| commits.contains(oid))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>()
+    };
+
+    let mut candidates = HashSet::new();
+    let mut exclusive = HashSet::new();
+    for oid in oids {
+        let peers = containing(oid);
+        if let [index] = peers.as_slice() {
+            exclusive.insert(*index);
+        }
+        candidates.extend(peers);
+    }
+
+    let single = |set: HashSet<usize>| {
+        if set.len() == 1 {
+            set.into_iter().next()
+        } else {
+            None
+        }
+    };
+    single(exclusive).or_else(|| single(candidates))
+}
+
+/// Start of the week containing `time`, both in seconds since the epoch.
+const fn week_start(time: i64) -> i64 {
+    time - (time + MONDAY_OFFSET).rem_euclid(WEEK)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+    use radicle_source::surf::vcs::git::git2;
+
+    #[test]
+    fn week_start() {
+        // Thursday, 1 January 1970.
+        assert_eq!(super::week_start(0), -3 * 24 * 60 * 60);
+        // Monday, 4 January 2021, 00:00 and Sunday, 10 January 2021, 23:59.
+        assert_eq!(super::week_start(1_609_718_400), 1_609_718_400);
+        assert_eq!(super::week_start(1_610_323_140), 1_609_718_400);
+    }
+
+    #[test]
+    fn attribute() {
+        let oid = |byte: u8| git2::Oid::from_bytes(&[byte; 20]).unwrap();
+        let shared = vec![oid(1), oid(2)].into_iter().collect::<HashSet<_>>();
+        let own = vec![oid(1), oid(2), oid(3)]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        // Only the second peer has the third commit.
+        assert_eq!(
+            super::attribute(&[oid(1), oid(3)], &[shared.clone(), own.clone()]),
+            Some(1)
+        );
+        // Everyone has the commits, so they can't be told apart.
+        assert_eq!(
+            super::attribute(&[oid(1)], &[shared.clone(), own.clone()]),
+            None
+        );
+        // A single peer has all of them.
+        assert_eq!(super::attribute(&[oid(1)], &[shared]), Some(0));
+        assert_eq!(super::attribute(&[oid(4)], &[own]), None);
+    }
+}
@@ -0,0 +1,291 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`write`] the tree of a commit as an archive, like `git archive`.
+
+use std::{
+    convert::TryFrom as _,
+    io::{self, Cursor, Write},
+};
+
+use chrono::{Datelike as _, NaiveDateTime, Timelike as _};
+use flate2::{write::GzEncoder, Compression};
+use link_crypto::BoxedSigner;
+use radicle_source::surf::vcs::git::git2;
+use serde::{Deserialize, Serialize};
+
+use crate::{browser, error::Error};
+
+/// File mode git uses for executable blobs.
+const MODE_EXECUTABLE: i32 = 0o100_755;
+/// File mode git uses for symbolic links.
+const MODE_LINK: i32 = 0o120_000;
+/// Zip archives are assembled in memory, so the blobs of a tree exported as zip must not exceed
+/// this many bytes in total.
+pub const MAX_ZIP_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Archive formats supported by [`write`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum Format {
+    /// A gzip compressed tarball.
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    /// A zip archive with deflate compression.
+    #[serde(rename = "zip")]
+    Zip,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::TarGz
+    }
+}
+
+impl Format {
+    /// Media type of archives in this format.
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::TarGz => "application/gzip",
+            Self::Zip => "application/zip",
+        }
+    }
+
+    /// File extension of archives in this format, without the leading dot.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::TarGz => "tar.gz",
+            Self::Zip => "zip",
+        }
+    }
+}
+
+/// A blob of the archived tree.
+struct Entry {
+    /// Path of the blob in the archive, including the prefix.
+    path: String,
+    /// Id of the blob.
+    oid: git2::Oid,
+    /// Git file mode of the blob.
+    mode: i32,
+}
+
+/// Check that the tree of `commit` can be archived in `format` under `prefix`, before [`write`]
+/// starts to produce output.
+///
+/// # Errors
+///   * [`Error::InvalidPath`] if the `prefix` is not a relative path, see [`check_prefix`].
+///   * If the tree of the commit could not be read.
+///   * [`Error::ArchiveTooLarge`] if the blobs of a tree exported as zip exceed [`MAX_ZIP_SIZE`].
+pub fn check(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    commit: git2::Oid,
+    format: Format,
+    prefix: &str,
+) -> Result<(), Error> {
+    check_prefix(prefix)?;
+    if format != Format::Zip {
+        return Ok(());
+    }
+
+    browser::using_repository(peer, |repo| {
+        let entries = entries(&repo.find_commit(commit)?, "")?;
+        check_zip_size(repo, &entries)
+    })
+}
+
+/// Write an archive of the tree of `commit` in `format` to `out`. Every path in the archive is
+/// prepended with `prefix`, pass a prefix ending in `/` to put all entries into a directory.
+/// Submodules are left out.
+///
+/// Tarballs are written to `out` while the tree is traversed, zip archives are assembled in
+/// memory first and limited to [`MAX_ZIP_SIZE`].
+///
+/// # Errors
+///   * [`Error::InvalidPath`] if the `prefix` is not a relative path, see [`check_prefix`].
+///   * If the tree of the commit could not be read.
+///   * [`Error::ArchiveTooLarge`] if the zip archive would exceed [`MAX_ZIP_SIZE`].
+///   * If writing to `out` fails.
+pub fn write<W>(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    commit: git2::Oid,
+    format: Format,
+    prefix: &str,
+    mut out: W,
+) -> Result<(), Error>
+where
+    W: Write + Send,
+{
+    check_prefix(prefix)?;
+
+    browser::using_repository(peer, |repo| {
+        let commit = repo.find_commit(commit)?;
+        let time = commit.committer().when().seconds();
+        let entries = entries(&commit, prefix)?;
+
+        match format {
+            Format::TarGz => {
+                let mut builder =
+                    tar::Builder::new(GzEncoder::new(&mut out, Compression::default()));
+                for entry in entries {
+                    let blob = repo.find_blob(entry.oid)?;
+                    let mut header = tar::Header::new_gnu();
+                    header.set_mtime(u64::try_from(time).unwrap_or_default());
+                    if entry.mode == MODE_LINK {
+                        header.set_entry_type(tar::EntryType::Symlink);
+                        header.set_mode(0o777);
+                        header.set_size(0);
+                        header.set_link_name(String::from_utf8_lossy(blob.content()).as_ref())?;
+                        builder.append_data(&mut header, &entry.path, io::empty())?;
+                    } else {
+                        header.set_entry_type(tar::EntryType::Regular);
+                        header.set_mode(mode(entry.mode));
+                        header.set_size(blob.content().len() as u64);
+                        builder.append_data(&mut header, &entry.path, blob.content())?;
+                    }
+                }
+                builder.into_inner()?.finish()?;
+            },
+            Format::Zip => {
+                check_zip_size(repo, &entries)?;
+
+                let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+                let options = zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(zip_time(time));
+                for entry in entries {
+                    let blob = repo.find_blob(entry.oid)?;
+                    zip.start_file(entry.path, options.unix_permissions(mode(entry.mode)))
+                        .map_err(io::Error::from)?;
+                    zip.write_all(blob.content())?;
+                }
+                let archive = zip.finish().map_err(io::Error::from)?;
+                out.write_all(archive.get_ref())?;
+            },
+        }
+        out.flush()?;
+
+        Ok(())
+    })
+}
+
+/// Check that the paths of the archive stay relative when `prefix` is prepended to them. Tar
+/// refuses other paths, but zip would write them as they are. The prefix may end in `/`, but may
+/// not start with one, nor contain empty, `.` or `..` components.
+///
+/// # Errors
+///   * [`Error::InvalidPath`] if the `prefix` is not a relative path.
+pub fn check_prefix(prefix: &str) -> Result<(), Error> {
+    if prefix.is_empty() {
+        return Ok(());
+    }
+
+    if prefix
+        .strip_suffix('/')
+        .unwrap_or(prefix)
+        .split('/')
+        .any(|component| matches!(component, "" | "." | ".."))
+    {
+        return Err(Error::InvalidPath(prefix.to_string()));
+    }
+
+    Ok(())
+}
+
+/// The blobs in the tree of `commit`, with their paths prepended with `prefix`.
+fn entries(commit: &git2::Commit, prefix: &str) -> Result<Vec<Entry>, Error> {
+    let mut entries = Vec::new();
+    commit
+        .tree()?
+        .walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    entries.push(Entry {
+                        path: format!("{}{}{}", prefix, dir, name),
+                        oid: entry.id(),
+                        mode: entry.filemode(),
+                    });
+                }
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+
+    Ok(entries)
+}
+
+/// Fail with [`Error::ArchiveTooLarge`] if the `entries` exceed [`MAX_ZIP_SIZE`]. Only the
+/// headers of the blobs are read.
+fn check_zip_size(repo: &git2::Repository, entries: &[Entry]) -> Result<(), Error> {
+    let odb = repo.odb()?;
+    let mut size = 0_u64;
+    for entry in entries {
+        let (blob_size, _) = odb.read_header(entry.oid)?;
+        size += blob_size as u64;
+        if size > MAX_ZIP_SIZE {
+            return Err(Error::ArchiveTooLarge(MAX_ZIP_SIZE));
+        }
+    }
+
+    Ok(())
+}
+
+/// Unix permissions for a blob with the git file `mode`.
+fn mode(mode: i32) -> u32 {
+    if mode == MODE_EXECUTABLE {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+/// Convert seconds since the epoch to the MS-DOS time stored in zip archives. Times outside of
+/// the range zip can represent fall back to the earliest representable time.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn zip_time(seconds: i64) -> zip::DateTime {
+    let time = match NaiveDateTime::from_timestamp_opt(seconds, 0) {
+        Some(time) => time,
+        None => return zip::DateTime::default(),
+    };
+    zip::DateTime::from_date_and_time(
+        time.year() as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn check_prefix() {
+        for prefix in &["", "project/", "project-", "a/b/", "a/b-"] {
+            assert!(super::check_prefix(prefix).is_ok(), "{}", prefix);
+        }
+        for prefix in &["/", "/etc/", "../x", "a/../../x", "a//b/", "./a/", "a/./"] {
+            assert!(super::check_prefix(prefix).is_err(), "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn zip_time() {
+        let date = |seconds| {
+            let time = super::zip_time(seconds);
+            (time.year(), time.month(), time.day(), time.hour())
+        };
+
+        assert_eq!(date(i64::MAX), (1980, 1, 1, 0));
+        assert_eq!(date(i64::MIN), (1980, 1, 1, 0));
+        // Before 1980, the start of MS-DOS time.
+        assert_eq!(date(0), (1980, 1, 1, 0));
+        assert_eq!(date(1_600_000_000), (2020, 9, 13, 12));
+    }
+}
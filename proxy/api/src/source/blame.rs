@@ -0,0 +1,96 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`blame`] the lines of a blob.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::Path,
+};
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use radicle_git_ext::Oid;
+use radicle_source::{commit::Header, surf::vcs::git::git2};
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// The commits that last changed the lines of a blob.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blame {
+    /// Consecutive line ranges of the blob, each last changed by a single commit.
+    pub hunks: Vec<Hunk>,
+    /// Headers of all commits referenced by [`Blame::hunks`], keyed by their id.
+    pub commits: HashMap<Oid, Header>,
+}
+
+/// A range of lines of a blob that was last changed by a single commit.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    /// First line of the range, starting at 1.
+    pub start_line: usize,
+    /// Number of lines in the range.
+    pub lines: usize,
+    /// Id of the commit that last changed the range. See [`Blame::commits`] for its header.
+    pub sha1: Oid,
+    /// Path of the blob in that commit, if it differs from the requested path.
+    pub path: Option<String>,
+}
+
+/// Blame the blob at `path` in `revision`, which is looked up in the namespace of `reference`.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If there is no blob at `path` in the revision.
+pub fn blame(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    path: &str,
+) -> Result<Blame, Error> {
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        match repo.find_commit(head)?.tree()?.get_path(Path::new(path)) {
+            Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {},
+            Ok(_) => return Err(Error::PathNotFound(path.to_string())),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Err(Error::PathNotFound(path.to_string()))
+            },
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut options = git2::BlameOptions::new();
+        options.newest_commit(head);
+        let blame = repo.blame_file(Path::new(path), Some(&mut options))?;
+
+        let mut hunks = Vec::with_capacity(blame.len());
+        let mut commits = HashMap::new();
+        for hunk in blame.iter() {
+            let sha1 = hunk.final_commit_id();
+            if let Entry::Vacant(entry) = commits.entry(Oid::from(sha1)) {
+                entry.insert(source::header(repo.find_commit(sha1)?)?);
+            }
+
+            hunks.push(Hunk {
+                start_line: hunk.final_start_line(),
+                lines: hunk.lines_in_hunk(),
+                sha1: sha1.into(),
+                path: hunk
+                    .path()
+                    .filter(|hunk_path| *hunk_path != Path::new(path))
+                    .map(|hunk_path| hunk_path.display().to_string()),
+            });
+        }
+
+        Ok(Blame { hunks, commits })
+    })
+}
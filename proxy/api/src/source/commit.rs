@@ -0,0 +1,183 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Find the branches of all peers [`containing`] a commit and the [`publishers`] of a commit.
+
+use std::str::FromStr as _;
+
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// A branch of a peer in the namespace of a project.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Branch {
+    /// The peer that published the branch, `None` for the local peer.
+    pub peer_id: Option<PeerId>,
+    /// Name of the branch, without the `refs/heads/` prefix.
+    pub name: String,
+}
+
+/// List the branches of the local peer and of all tracked remotes of the project `urn` that
+/// contain `commit`. Branches of the local peer come first, the rest is sorted by peer and name.
+///
+/// # Errors
+///   * If the references of the project could not be read.
+pub fn containing(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    commit: git2::Oid,
+) -> Result<Vec<Branch>, Error> {
+    let prefix = source::refs_prefix(urn, None);
+
+    browser::using_repository(peer, |repo| {
+        let mut branches = Vec::new();
+        for reference in repo.references_glob(&format!("{}*", prefix))? {
+            let reference = reference?;
+            let branch = match reference
+                .name()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(branch)
+            {
+                Some(branch) => branch,
+                None => continue,
+            };
+
+            if contains(repo, &reference, commit)? {
+                branches.push(branch);
+            }
+        }
+        branches.sort_by_key(|branch| {
+            (
+                branch.peer_id.map(|peer_id| peer_id.to_string()),
+                branch.name.clone(),
+            )
+        });
+
+        Ok(branches)
+    })
+}
+
+/// List the peers that published a branch or a tag of the project `urn` containing `commit`.
+/// The local peer comes first as `None`, the rest is sorted. Tags include the ones of patches, so
+/// commits that were only ever proposed in a patch are found as well.
+///
+/// # Errors
+///   * If the references of the project could not be read.
+pub fn publishers(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    commit: git2::Oid,
+) -> Result<Vec<Option<PeerId>>, Error> {
+    let prefix = source::refs_prefix(urn, None);
+
+    browser::using_repository(peer, |repo| {
+        let mut peers = Vec::new();
+        for reference in repo.references_glob(&format!("{}*", prefix))? {
+            let reference = reference?;
+            let publisher = match reference
+                .name()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(publisher)
+            {
+                Some(publisher) => publisher,
+                None => continue,
+            };
+
+            if !peers.contains(&publisher) && contains(repo, &reference, commit)? {
+                peers.push(publisher);
+            }
+        }
+        peers.sort_by_key(|peer_id| peer_id.map(|peer_id| peer_id.to_string()));
+
+        Ok(peers)
+    })
+}
+
+/// Returns `true` if `commit` is the commit `reference` points to or one of its ancestors.
+/// References that don't point to a commit contain nothing.
+fn contains(
+    repo: &git2::Repository,
+    reference: &git2::Reference,
+    commit: git2::Oid,
+) -> Result<bool, Error> {
+    let tip = match reference.peel_to_commit() {
+        Ok(tip) => tip.id(),
+        Err(_) => return Ok(false),
+    };
+
+    Ok(tip == commit || repo.graph_descendant_of(tip, commit)?)
+}
+
+/// Parse a reference relative to the `refs/` of a project namespace into a [`Branch`]. Returns
+/// `None` for references that are not branches.
+pub(super) fn branch(name: &str) -> Option<Branch> {
+    if let Some(name) = name.strip_prefix("heads/") {
+        return Some(Branch {
+            peer_id: None,
+            name: name.to_string(),
+        });
+    }
+
+    let remote = name.strip_prefix("remotes/")?;
+    let (peer_id, rest) = remote.split_at(remote.find('/')?);
+    let name = rest.strip_prefix("/heads/")?;
+
+    Some(Branch {
+        peer_id: Some(PeerId::from_str(peer_id).ok()?),
+        name: name.to_string(),
+    })
+}
+
+/// Parse a reference relative to the `refs/` of a project namespace into the peer that
+/// published it, `None` for the local peer. Returns `None` for references that are neither
+/// branches nor tags.
+fn publisher(name: &str) -> Option<Option<PeerId>> {
+    if name.starts_with("heads/") || name.starts_with("tags/") {
+        return Some(None);
+    }
+
+    let remote = name.strip_prefix("remotes/")?;
+    let (peer_id, rest) = remote.split_at(remote.find('/')?);
+    if !rest.starts_with("/heads/") && !rest.starts_with("/tags/") {
+        return None;
+    }
+
+    Some(Some(PeerId::from_str(peer_id).ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use link_crypto::{PeerId, SecretKey};
+
+    #[test]
+    fn publisher() {
+        let peer_id = PeerId::from(SecretKey::new());
+
+        assert_eq!(super::publisher("heads/master"), Some(None));
+        assert_eq!(super::publisher("tags/radicle-patch/fix"), Some(None));
+        assert_eq!(
+            super::publisher(&format!("remotes/{}/heads/dev", peer_id)),
+            Some(Some(peer_id))
+        );
+        assert_eq!(
+            super::publisher(&format!("remotes/{}/tags/radicle-patch/fix", peer_id)),
+            Some(Some(peer_id))
+        );
+        assert_eq!(super::publisher("rad/id"), None);
+        assert_eq!(
+            super::publisher(&format!("remotes/{}/rad/id", peer_id)),
+            None
+        );
+        assert_eq!(super::publisher("remotes/not-a-peer/heads/master"), None);
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`compare`] two revisions of a project.
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use radicle_git_ext::Oid;
+use radicle_source::{
+    commit::Header,
+    surf::{diff::Diff, vcs::git::git2},
+};
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// The changes a `head` revision introduces over a `base` revision.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    /// Commit the `base` revision resolved to.
+    pub base: Oid,
+    /// Commit the `head` revision resolved to.
+    pub head: Oid,
+    /// The merge base of [`Comparison::base`] and [`Comparison::head`]. The diff is computed
+    /// against it, so that changes to `base` since `head` diverged don't show up.
+    pub merge_base: Option<Oid>,
+    /// Commits reachable from `head` but not from `base`, newest first.
+    pub commits: Vec<Header>,
+    /// Combined changes of all [`Comparison::commits`].
+    pub diff: Diff,
+    /// Line statistics of [`Comparison::diff`].
+    pub stats: source::DiffStats,
+}
+
+/// Compare the `head` revision against the `base` revision. Both revisions are looked up in the
+/// namespace of `reference`, a `None` revision stands for the head of `reference` itself.
+///
+/// # Errors
+///   * If either revision could not be resolved.
+///   * If the history between the revisions could not be walked.
+pub fn compare(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    base: Option<radicle_source::Revision<PeerId>>,
+    head: Option<radicle_source::Revision<PeerId>>,
+) -> Result<Comparison, Error> {
+    let (base, head, merge_base, diff) = browser::using(peer, reference, |browser| {
+        let base = browser::resolve(browser, base)?;
+        let head = browser::resolve(browser, head)?;
+        let merge_base = browser.merge_base(base, head)?;
+        let diff = browser.diff(merge_base.unwrap_or(base), head)?;
+
+        Ok((base, head, merge_base, diff))
+    })?;
+
+    let commits = browser::using_repository(peer, |repo| {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push(head)?;
+        revwalk.hide(base)?;
+
+        revwalk
+            .map(|oid| source::header(repo.find_commit(oid?)?))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    Ok(Comparison {
+        base: base.into(),
+        head: head.into(),
+        merge_base: merge_base.map(Oid::from),
+        commits,
+        stats: source::DiffStats::from(&diff),
+        diff,
+    })
+}
@@ -0,0 +1,141 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! The commit [`graph`] spanned by the branches of several peers.
+
+use std::collections::HashMap;
+
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::{commit::Header, surf::vcs::git::git2};
+use serde::Serialize;
+
+use crate::{
+    browser,
+    error::Error,
+    source::{self, commit::Branch},
+};
+
+/// Number of commits returned if the request doesn't set a limit.
+pub const DEFAULT_LIMIT: usize = 200;
+/// Upper bound for the number of commits a single graph contains.
+pub const MAX_LIMIT: usize = 2000;
+
+/// Commits reachable from the branches of a set of peers, in topological order.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Graph {
+    /// The commits, children before their parents.
+    pub commits: Vec<Node>,
+    /// `true` if the limit was hit and older commits were left out. Parents of the last commits
+    /// may then be missing from [`Graph::commits`].
+    pub truncated: bool,
+}
+
+/// A commit in the [`Graph`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    /// The commit.
+    #[serde(flatten)]
+    pub header: Header,
+    /// Ids of the parents of the commit, the first parent first.
+    pub parents: Vec<Oid>,
+    /// Branches pointing at the commit, local branches first.
+    pub branches: Vec<Branch>,
+}
+
+/// List the branches of `peers` in the namespace of the project `urn` with the commits they point
+/// to, where `None` stands for the local peer. The branches of all peers are included if `peers`
+/// is `None`. Sorted by peer and name, the local peer first.
+///
+/// # Errors
+///   * If the references of the project could not be read.
+pub fn tips(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    peers: Option<&[Option<PeerId>]>,
+) -> Result<Vec<(Branch, git2::Oid)>, Error> {
+    let prefix = source::refs_prefix(urn, None);
+
+    browser::using_repository(peer, |repo| {
+        let mut tips = Vec::new();
+        for reference in repo.references_glob(&format!("{}*", prefix))? {
+            let reference = reference?;
+            let branch = match reference
+                .name()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(source::commit::branch)
+            {
+                Some(branch) => branch,
+                None => continue,
+            };
+            if !peers.map_or(true, |peers| peers.contains(&branch.peer_id)) {
+                continue;
+            }
+            if let Ok(tip) = reference.peel_to_commit() {
+                tips.push((branch, tip.id()));
+            }
+        }
+        tips.sort_by_key(|(branch, _)| {
+            (
+                branch.peer_id.map(|peer_id| peer_id.to_string()),
+                branch.name.clone(),
+            )
+        });
+
+        Ok(tips)
+    })
+}
+
+/// Build the graph of the commits reachable from `tips`, as listed by [`tips`].
+///
+/// At most `limit` commits are returned, [`DEFAULT_LIMIT`] if `None`. Capped at [`MAX_LIMIT`].
+///
+/// # Errors
+///   * If the history could not be walked.
+pub fn graph(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    tips: Vec<(Branch, git2::Oid)>,
+    limit: Option<usize>,
+) -> Result<Graph, Error> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    browser::using_repository(peer, |repo| {
+        // The branches keep the order of `tips`.
+        let mut branches = HashMap::<git2::Oid, Vec<Branch>>::new();
+        for (branch, tip) in tips {
+            branches.entry(tip).or_default().push(branch);
+        }
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        for tip in branches.keys() {
+            revwalk.push(*tip)?;
+        }
+
+        let mut commits = Vec::new();
+        let mut truncated = false;
+        for oid in revwalk {
+            if commits.len() == limit {
+                truncated = true;
+                break;
+            }
+
+            let commit = repo.find_commit(oid?)?;
+            let parents = commit.parent_ids().map(Oid::from).collect();
+
+            commits.push(Node {
+                branches: branches.remove(&commit.id()).unwrap_or_default(),
+                header: source::header(commit)?,
+                parents,
+            });
+        }
+
+        Ok(Graph { commits, truncated })
+    })
+}
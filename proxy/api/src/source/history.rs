@@ -0,0 +1,282 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Walk the [`commits`] of a revision page by page, optionally filtered, or follow the history of
+//! a single [`file`].
+
+use std::path::Path;
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use radicle_git_ext::Oid;
+use radicle_source::{
+    commit::Header,
+    surf::vcs::git::{git2, Stats},
+};
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// Criteria a commit has to meet to be part of [`Commits`].
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Only include commits where the author name or email contains this string, ignoring case.
+    pub author: Option<String>,
+    /// Only include commits committed at or after this time, in seconds since the epoch.
+    pub since: Option<i64>,
+    /// Only include commits committed at or before this time, in seconds since the epoch.
+    pub until: Option<i64>,
+    /// Only include commits that changed the entry at this path.
+    pub path: Option<String>,
+}
+
+impl Filter {
+    /// Returns `true` if `commit` meets all criteria of the filter.
+    fn matches(&self, commit: &git2::Commit) -> Result<bool, Error> {
+        if let Some(author) = &self.author {
+            let author = author.to_lowercase();
+            let signature = commit.author();
+            let name = signature.name().unwrap_or_default().to_lowercase();
+            let email = signature.email().unwrap_or_default().to_lowercase();
+            if !name.contains(&author) && !email.contains(&author) {
+                return Ok(false);
+            }
+        }
+
+        let time = commit.committer().when().seconds();
+        if self.since.map_or(false, |since| time < since)
+            || self.until.map_or(false, |until| time > until)
+        {
+            return Ok(false);
+        }
+
+        match &self.path {
+            Some(path) => touches(commit, Path::new(path)),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Position in the history to start a page at and the amount of commits to return.
+#[derive(Debug, Default)]
+pub struct Page {
+    /// Maximum number of commits to return, all remaining commits if `None`.
+    pub limit: Option<usize>,
+    /// Start the page with the commit following this one in the history. This is the
+    /// [`Commits::next`] cursor of the previous page.
+    pub after: Option<Oid>,
+}
+
+/// A page of the history of a revision.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Commits {
+    /// Headers of the commits on this page, newest first.
+    pub headers: Vec<Header>,
+    /// Statistics of the whole history of the revision. Computing them walks the whole history,
+    /// so they are only present on the first page.
+    pub stats: Option<Stats>,
+    /// Cursor to pass as [`Page::after`] to get the next page, `None` if this is the last page.
+    pub next: Option<Oid>,
+}
+
+/// Get a page of the history of `revision` with all commits that meet the `filter`. The
+/// revision is looked up in the namespace of `reference`.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If the cursor of the `page` is not in the history of the revision.
+///   * If the history could not be walked.
+pub fn commits(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    filter: &Filter,
+    page: &Page,
+) -> Result<Commits, Error> {
+    let (head, stats) = browser::using(peer, reference, |browser| {
+        let head = browser::resolve(browser, revision)?;
+        let stats = match page.after {
+            Some(_) => None,
+            None => Some(browser.get_stats()?),
+        };
+        Ok((head, stats))
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        if let Some(after) = page.after {
+            if !in_history(repo, head, *after) {
+                return Err(Error::InvalidCursor(after));
+            }
+        }
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push(head)?;
+
+        // Resuming from the parents of the cursor would lose the other sides of merges, so the
+        // walk starts over at the head and skips everything up to and including the cursor.
+        let mut skipping = page.after.map(|after| *after);
+        let mut headers = Vec::new();
+        let mut last = None;
+        let mut next = None;
+        for oid in revwalk {
+            let oid = oid?;
+            if let Some(after) = skipping {
+                if oid == after {
+                    skipping = None;
+                }
+                continue;
+            }
+            let commit = repo.find_commit(oid)?;
+            if !filter.matches(&commit)? {
+                continue;
+            }
+
+            if page.limit.map_or(false, |limit| headers.len() >= limit) {
+                next = last.map(Oid::from);
+                break;
+            }
+            headers.push(source::header(commit)?);
+            last = Some(oid);
+        }
+
+        Ok(Commits {
+            headers,
+            stats,
+            next,
+        })
+    })
+}
+
+/// Returns `true` if `commit` is `head` or one of its ancestors. Commits that are missing from
+/// the monorepo are in no history.
+fn in_history(repo: &git2::Repository, head: git2::Oid, commit: git2::Oid) -> bool {
+    commit == head || repo.graph_descendant_of(head, commit).unwrap_or(false)
+}
+
+/// A commit in the history of a [`file`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// The commit that changed the entry.
+    pub header: Header,
+    /// Path of the entry in this commit. For commits older than a rename this is the path before
+    /// the rename.
+    pub path: String,
+    /// Path the entry had in the parent commit if this commit renamed it.
+    pub previous_path: Option<String>,
+}
+
+/// List the commits in the history of `revision` that changed the entry at `path`, newest first.
+/// Renames of files are followed, so the history continues under the previous path of a file.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If there is no entry at `path` in the revision.
+///   * If the history could not be walked.
+pub fn file(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    path: &str,
+) -> Result<Vec<Change>, Error> {
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        let is_file = match repo.find_commit(head)?.tree()?.get_path(Path::new(path)) {
+            Ok(entry) => entry.kind() == Some(git2::ObjectType::Blob),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Err(Error::PathNotFound(path.to_string()))
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push(head)?;
+
+        let mut path = path.to_string();
+        let mut changes = Vec::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            if !touches(&commit, Path::new(&path))? {
+                continue;
+            }
+
+            let previous_path = if is_file {
+                renamed_from(repo, &commit, &path)?
+            } else {
+                None
+            };
+            changes.push(Change {
+                header: source::header(commit)?,
+                path: path.clone(),
+                previous_path: previous_path.clone(),
+            });
+
+            if let Some(previous_path) = previous_path {
+                path = previous_path;
+            }
+        }
+
+        Ok(changes)
+    })
+}
+
+/// If `commit` renamed a file to `path` compared to its first parent, returns the path the file
+/// had before.
+fn renamed_from(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    path: &str,
+) -> Result<Option<String>, Error> {
+    let parent = match commit.parents().next() {
+        Some(parent) => parent,
+        None => return Ok(None),
+    };
+    // Only commits that added the path can have renamed a file to it.
+    if parent.tree()?.get_path(Path::new(path)).is_ok() {
+        return Ok(None);
+    }
+
+    let mut diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&commit.tree()?), None)?;
+    diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+
+    let previous_path = diff
+        .deltas()
+        .filter(|delta| delta.status() == git2::Delta::Renamed)
+        .find(|delta| delta.new_file().path() == Some(Path::new(path)))
+        .and_then(|delta| {
+            delta
+                .old_file()
+                .path()
+                .map(|path| path.display().to_string())
+        });
+
+    Ok(previous_path)
+}
+
+/// Returns `true` if `commit` changed the entry at `path` compared to its parents. Like `git log
+/// <path>`, merges that took the entry unchanged from one of their parents don't count.
+pub(super) fn touches(commit: &git2::Commit, path: &Path) -> Result<bool, Error> {
+    let entry = |tree: git2::Tree| tree.get_path(path).ok().map(|entry| entry.id());
+
+    let current = entry(commit.tree()?);
+    if commit.parent_count() == 0 {
+        return Ok(current.is_some());
+    }
+
+    for parent in commit.parents() {
+        if entry(parent.tree()?) == current {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,219 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`breakdown`] of the blobs in a tree by [`Language`].
+
+use std::{collections::HashMap, path::Path};
+
+use lazy_static::lazy_static;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use super::SYNTAXES;
+use crate::{cache::Cache, error::Error};
+
+lazy_static! {
+    /// Breakdowns by the id of the tree they were computed for.
+    static ref BREAKDOWNS: Cache<git2::Oid, Vec<Language>> = Cache::new(1024);
+}
+
+/// Attributes of paths that are left out of the breakdown, as used by GitHub linguist.
+const EXCLUDING_ATTRIBUTES: &[&str] = &["linguist-vendored", "linguist-generated"];
+
+/// Name of the syntax assigned to blobs without a more specific one, which is not counted.
+const PLAIN_TEXT: &str = "Plain Text";
+
+/// The share of a language in a tree.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Language {
+    /// Name of the language.
+    pub name: String,
+    /// Accumulated size of all blobs in the language.
+    pub bytes: usize,
+    /// Number of blobs in the language.
+    pub files: usize,
+}
+
+/// Count the bytes and blobs per language in `tree`, sorted by bytes in descending order. The
+/// language of a blob is detected from its extension or name.
+///
+/// Blobs marked as `linguist-vendored` or `linguist-generated` in a `.gitattributes` file of the
+/// tree are skipped. Results are cached by the id of `tree`.
+///
+/// # Errors
+///   * If the tree could not be walked.
+pub fn breakdown(repo: &git2::Repository, tree: &git2::Tree) -> Result<Vec<Language>, Error> {
+    BREAKDOWNS.get_or_try_insert_with(tree.id(), || compute(repo, tree))
+}
+
+/// Compute the [`breakdown`] of `tree`.
+fn compute(repo: &git2::Repository, tree: &git2::Tree) -> Result<Vec<Language>, Error> {
+    let mut blobs = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            if let Some(name) = entry.name() {
+                blobs.push((format!("{}{}", dir, name), entry.id()));
+            }
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+
+    // The monorepo is bare, so attributes are read from an index of `tree`. The index is set on a
+    // handle of its own to leave the pooled one untouched.
+    let attributes = git2::Repository::open_bare(repo.path())?;
+    let mut index = git2::Index::new()?;
+    index.read_tree(tree)?;
+    attributes.set_index(&mut index);
+
+    let odb = repo.odb()?;
+    let mut languages = HashMap::<&str, Language>::new();
+    for (path, oid) in &blobs {
+        let name = match language(path) {
+            Some(name) => name,
+            None => continue,
+        };
+        if is_excluded(&attributes, path)? {
+            continue;
+        }
+        let (size, _) = odb.read_header(*oid)?;
+
+        let language = languages.entry(name).or_insert_with(|| Language {
+            name: name.to_string(),
+            bytes: 0,
+            files: 0,
+        });
+        language.bytes += size;
+        language.files += 1;
+    }
+
+    let mut languages = languages.into_values().collect::<Vec<_>>();
+    languages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(languages)
+}
+
+/// Detect the language of the blob at `path` from its extension, or its name for files like
+/// `Makefile`. Returns `None` if there is no syntax definition for it.
+fn language(path: &str) -> Option<&'static str> {
+    let path = Path::new(path);
+    let syntax = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| SYNTAXES.find_syntax_by_extension(extension))
+        .or_else(|| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| SYNTAXES.find_syntax_by_extension(name))
+        })?;
+
+    if syntax.name == PLAIN_TEXT {
+        None
+    } else {
+        Some(syntax.name.as_str())
+    }
+}
+
+/// Returns `true` if one of the [`EXCLUDING_ATTRIBUTES`] is set for `path` in the `.gitattributes`
+/// files of the index of `repo`.
+fn is_excluded(repo: &git2::Repository, path: &str) -> Result<bool, Error> {
+    for attribute in EXCLUDING_ATTRIBUTES {
+        let value = repo.get_attr(
+            Path::new(path),
+            attribute,
+            git2::AttrCheckFlags::INDEX_ONLY | git2::AttrCheckFlags::NO_SYSTEM,
+        )?;
+        let set = match git2::AttrValue::from_string(value) {
+            git2::AttrValue::True => true,
+            git2::AttrValue::String(value) => value != "false",
+            git2::AttrValue::Bytes(value) => value != b"false",
+            git2::AttrValue::False | git2::AttrValue::Unspecified => false,
+        };
+        if set {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use radicle_source::surf::vcs::git::git2;
+
+    use super::Language;
+
+    /// Write a tree with the blobs in `files`, given as path and content, to `repo`.
+    fn tree(repo: &git2::Repository, files: &[(&str, &str)]) -> Result<git2::Oid, git2::Error> {
+        let mut index = git2::Index::new()?;
+        for (path, content) in files {
+            let id = repo.blob(content.as_bytes())?;
+            index.add(&git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100_644,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id,
+                flags: 0,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            })?;
+        }
+
+        index.write_tree_to(repo)
+    }
+
+    #[test]
+    fn language() {
+        assert_eq!(super::language("src/lib.rs"), Some("Rust"));
+        assert_eq!(super::language("Makefile"), Some("Makefile"));
+        assert_eq!(super::language("text/arrows.txt"), None);
+        assert_eq!(super::language("bin/cat"), None);
+    }
+
+    #[test]
+    fn excluded() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let repo = git2::Repository::init_bare(tmp_dir.path())?;
+        let id = tree(
+            &repo,
+            &[
+                (
+                    ".gitattributes",
+                    "# vendored code\n\
+                     vendor/** linguist-vendored\n\
+                     *.pb.rs linguist-generated=true\n\
+                     *.txt text eol=lf\n",
+                ),
+                ("src/api.pb.rs", "struct Api;\n"),
+                ("src/lib.rs", "mod api;\n"),
+                ("vendor/lib/app.rs", "fn app() {}\n"),
+                ("vendor/own/.gitattributes", "* -linguist-vendored\n"),
+                ("vendor/own/lib.rs", "fn own() {}\n"),
+            ],
+        )?;
+        let tree = repo.find_tree(id)?;
+
+        let languages = super::compute(&repo, &tree)?;
+        assert_eq!(
+            languages
+                .into_iter()
+                .find(|language| language.name == "Rust"),
+            Some(Language {
+                name: "Rust".to_string(),
+                bytes: 21,
+                files: 2,
+            })
+        );
+
+        Ok(())
+    }
+}
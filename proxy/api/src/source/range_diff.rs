@@ -0,0 +1,385 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Compare two [`series`] of commits, like `git range-diff`, see [`range_diff`].
+
+use std::collections::HashSet;
+
+use radicle_source::{commit::Header, surf::vcs::git::git2};
+use serde::Serialize;
+
+use crate::{error::Error, source};
+
+/// A commit of the old series and the corresponding one of the new series.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pair {
+    /// How the change of the commit changed.
+    pub status: Status,
+    /// The commit in the old series, `None` if it was [`Status::Added`].
+    pub old: Option<Header>,
+    /// The commit in the new series, `None` if it was [`Status::Removed`].
+    pub new: Option<Header>,
+    /// Diff of the changes of [`Pair::old`] to the changes of [`Pair::new`], only present for
+    /// [`Status::Modified`] pairs.
+    pub interdiff: Option<String>,
+}
+
+/// Outcome of matching a commit of the old series with one of the new series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    /// Both commits introduce the same changes.
+    Unchanged,
+    /// The commits have the same summary, but introduce different changes.
+    Modified,
+    /// The commit only exists in the new series.
+    Added,
+    /// The commit only exists in the old series.
+    Removed,
+}
+
+/// The changes a commit introduces, stripped of anything that differs when the same change is
+/// applied to another base.
+struct Change {
+    /// The commit.
+    id: git2::Oid,
+    /// First line of the commit message.
+    summary: String,
+    /// Patch of the commit against its first parent, without blob ids and line numbers.
+    patch: String,
+}
+
+/// The commits reachable from `head` but not from `base`, oldest first.
+///
+/// # Errors
+///   * If the history could not be walked.
+pub fn series(
+    repo: &git2::Repository,
+    base: git2::Oid,
+    head: git2::Oid,
+) -> Result<Vec<git2::Oid>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    revwalk.push(head)?;
+    revwalk.hide(base)?;
+
+    revwalk.collect()
+}
+
+/// Pair up the commits of the `old` series with the ones of the `new` series. Commits that
+/// introduce the same changes are paired first, then the remaining ones with the same summary.
+///
+/// The pairs follow the order of the `new` series, removed commits are placed where they were in
+/// the `old` one.
+///
+/// # Errors
+///   * If a commit or its changes could not be read.
+pub fn range_diff(
+    repo: &git2::Repository,
+    old: &[git2::Oid],
+    new: &[git2::Oid],
+) -> Result<Vec<Pair>, Error> {
+    let old_changes = old
+        .iter()
+        .map(|id| change(repo, *id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let new_changes = new
+        .iter()
+        .map(|id| change(repo, *id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let header = |change: Option<&Change>| {
+        change
+            .map(|change| source::header(repo.find_commit(change.id)?))
+            .transpose()
+    };
+
+    order(&matches(&old_changes, &new_changes), old_changes.len())
+        .into_iter()
+        .map(|(old, new)| -> Result<Pair, Error> {
+            let old = old.and_then(|index| old_changes.get(index));
+            let new = new.and_then(|index| new_changes.get(index));
+            let (status, interdiff) = match (old, new) {
+                (Some(old), Some(new)) if old.patch == new.patch => (Status::Unchanged, None),
+                (Some(old), Some(new)) => (Status::Modified, Some(interdiff(old, new)?)),
+                (None, _) => (Status::Added, None),
+                (_, None) => (Status::Removed, None),
+            };
+
+            Ok(Pair {
+                status,
+                old: header(old)?,
+                new: header(new)?,
+                interdiff,
+            })
+        })
+        .collect()
+}
+
+/// Read the [`Change`] introduced by the commit `id`.
+fn change(repo: &git2::Repository, id: git2::Oid) -> Result<Change, git2::Error> {
+    let commit = repo.find_commit(id)?;
+    let parent = commit
+        .parents()
+        .next()
+        .map(|parent| parent.tree())
+        .transpose()?;
+    let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), None)?;
+
+    let mut patch = String::new();
+    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+        match line.origin() {
+            'F' => {
+                let path = delta.new_file().path().or_else(|| delta.old_file().path());
+                if let Some(path) = path {
+                    patch.push_str(&format!("## {}\n", path.display()));
+                }
+            },
+            'H' => patch.push_str("@@\n"),
+            origin => {
+                if let '+' | '-' | ' ' = origin {
+                    patch.push(origin);
+                }
+                patch.push_str(&String::from_utf8_lossy(line.content()));
+            },
+        }
+        true
+    })?;
+
+    Ok(Change {
+        id,
+        summary: commit.summary().unwrap_or_default().to_string(),
+        patch,
+    })
+}
+
+/// Unified diff from the changes of `old` to the ones of `new`.
+fn interdiff(old: &Change, new: &Change) -> Result<String, git2::Error> {
+    let mut patch =
+        git2::Patch::from_buffers(old.patch.as_bytes(), None, new.patch.as_bytes(), None, None)?;
+
+    Ok(patch.to_buf()?.as_str().unwrap_or_default().to_string())
+}
+
+/// For every commit of the `new` series, the index of the commit of the `old` series it pairs
+/// with.
+fn matches(old: &[Change], new: &[Change]) -> Vec<Option<usize>> {
+    let same_patch: fn(&Change, &Change) -> bool = |old, new| old.patch == new.patch;
+    let same_summary: fn(&Change, &Change) -> bool = |old, new| old.summary == new.summary;
+
+    let mut paired = vec![false; old.len()];
+    let mut matches = vec![None; new.len()];
+    for same in &[same_patch, same_summary] {
+        for (new, matched) in new.iter().zip(matches.iter_mut()) {
+            if matched.is_some() {
+                continue;
+            }
+            if let Some(index) = (0..old.len()).find(|index| {
+                !paired.get(*index).copied().unwrap_or(true)
+                    && old.get(*index).map_or(false, |old| same(old, new))
+            }) {
+                if let Some(paired) = paired.get_mut(index) {
+                    *paired = true;
+                }
+                *matched = Some(index);
+            }
+        }
+    }
+
+    matches
+}
+
+/// Order the `matches` of the new series into pairs of old and new indices, with the unmatched
+/// commits of the old series placed before the first new commit that follows them.
+fn order(matches: &[Option<usize>], old_len: usize) -> Vec<(Option<usize>, Option<usize>)> {
+    let matched = matches.iter().flatten().copied().collect::<HashSet<_>>();
+
+    let mut pairs = Vec::new();
+    let mut next_old = 0;
+    for (new, old) in matches.iter().enumerate() {
+        if let Some(old) = old {
+            for removed in next_old..*old {
+                if !matched.contains(&removed) {
+                    pairs.push((Some(removed), None));
+                }
+            }
+            next_old = next_old.max(old + 1);
+        }
+        pairs.push((*old, Some(new)));
+    }
+    for removed in next_old..old_len {
+        if !matched.contains(&removed) {
+            pairs.push((Some(removed), None));
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use radicle_source::surf::vcs::git::git2;
+
+    use super::{Change, Status};
+
+    fn change(summary: &str, patch: &str) -> Change {
+        Change {
+            id: git2::Oid::zero(),
+            summary: summary.to_string(),
+            patch: patch.to_string(),
+        }
+    }
+
+    /// Commit the `files` as the whole tree on top of `parent`.
+    fn commit(
+        repo: &git2::Repository,
+        parent: Option<git2::Oid>,
+        summary: &str,
+        files: &[(&str, &str)],
+    ) -> Result<git2::Oid, git2::Error> {
+        let mut builder = repo.treebuilder(None)?;
+        for (path, content) in files {
+            builder.insert(path, repo.blob(content.as_bytes())?, 0o100_644)?;
+        }
+        let tree = repo.find_tree(builder.write()?)?;
+        let parents = parent
+            .map(|parent| repo.find_commit(parent))
+            .transpose()?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let parents = parents.iter().collect::<Vec<_>>();
+        let signature = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz")?;
+
+        repo.commit(None, &signature, &signature, summary, &tree, &parents)
+    }
+
+    #[test]
+    fn matches() {
+        let old = vec![
+            change("Add a", "+a\n"),
+            change("Add b", "+b\n"),
+            change("Add c", "+c\n"),
+        ];
+        let new = vec![
+            change("Add c", "+c\n"),
+            change("Add a", "+a\n+a\n"),
+            change("Add d", "+d\n"),
+            // Reworded, but the same change.
+            change("Add a b", "+b\n"),
+        ];
+
+        assert_eq!(
+            super::matches(&old, &new),
+            vec![Some(2), Some(0), None, Some(1)]
+        );
+    }
+
+    #[test]
+    fn order() {
+        assert_eq!(
+            super::order(&[Some(0), None, Some(2)], 4),
+            vec![
+                (Some(0), Some(0)),
+                (None, Some(1)),
+                (Some(1), None),
+                (Some(2), Some(2)),
+                (Some(3), None),
+            ]
+        );
+        // Reordered commits.
+        assert_eq!(
+            super::order(&[Some(1), Some(0)], 2),
+            vec![(Some(1), Some(0)), (Some(0), Some(1))]
+        );
+        assert_eq!(super::order(&[], 1), vec![(Some(0), None)]);
+    }
+
+    #[test]
+    fn range_diff() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let repo = git2::Repository::init_bare(tmp_dir.path())?;
+
+        let base = commit(&repo, None, "Count", &[("list", "1\n2\n3\n")])?;
+        let a = commit(&repo, Some(base), "Add four", &[("list", "1\n2\n3\n4\n")])?;
+        let b = commit(
+            &repo,
+            Some(a),
+            "Add b",
+            &[("b", "b\n"), ("list", "1\n2\n3\n4\n")],
+        )?;
+        let c = commit(
+            &repo,
+            Some(b),
+            "Add c",
+            &[("b", "b\n"), ("c", "c\n"), ("list", "1\n2\n3\n4\n")],
+        )?;
+
+        // Rebased on a base that shifts the lines of `list`, with `b` amended, `c` dropped and
+        // `d` added.
+        let rebased = commit(
+            &repo,
+            Some(base),
+            "Count from zero",
+            &[("list", "0\n1\n2\n3\n")],
+        )?;
+        let new_a = commit(
+            &repo,
+            Some(rebased),
+            "Add four",
+            &[("list", "0\n1\n2\n3\n4\n")],
+        )?;
+        let new_b = commit(
+            &repo,
+            Some(new_a),
+            "Add b",
+            &[("b", "b\nbb\n"), ("list", "0\n1\n2\n3\n4\n")],
+        )?;
+        let d = commit(
+            &repo,
+            Some(new_b),
+            "Add d",
+            &[("b", "b\nbb\n"), ("d", "d\n"), ("list", "0\n1\n2\n3\n4\n")],
+        )?;
+
+        let old = super::series(&repo, base, c)?;
+        let new = super::series(&repo, rebased, d)?;
+        assert_eq!(old, vec![a, b, c]);
+        assert_eq!(new, vec![new_a, new_b, d]);
+
+        let pairs = super::range_diff(&repo, &old, &new)?;
+        let have = pairs
+            .iter()
+            .map(|pair| {
+                (
+                    pair.status,
+                    pair.old.as_ref().map(|header| header.sha1),
+                    pair.new.as_ref().map(|header| header.sha1),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            have,
+            vec![
+                // Only the line numbers and blob ids differ.
+                (Status::Unchanged, Some(a), Some(new_a)),
+                (Status::Modified, Some(b), Some(new_b)),
+                (Status::Added, None, Some(d)),
+                (Status::Removed, Some(c), None),
+            ]
+        );
+        assert!(pairs
+            .iter()
+            .all(|pair| { (pair.status == Status::Modified) == pair.interdiff.is_some() }));
+        assert!(pairs
+            .iter()
+            .filter_map(|pair| pair.interdiff.as_ref())
+            .all(|interdiff| interdiff.contains("++bb")));
+
+        Ok(())
+    }
+}
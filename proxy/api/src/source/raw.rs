@@ -0,0 +1,112 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Access the [`raw`] content of a blob.
+
+use std::path::Path;
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use mime_guess::mime;
+use radicle_source::surf::vcs::git::git2;
+
+use crate::{browser, error::Error};
+
+/// Blobs are read into memory to be served, so they must not exceed this many bytes.
+pub const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Media type used for binary blobs we couldn't identify.
+const OCTET_STREAM: &str = "application/octet-stream";
+/// Media type used for text blobs we couldn't identify.
+const PLAIN_TEXT: &str = "text/plain; charset=utf-8";
+
+/// Leading bytes of common binary formats and their media types. Used for blobs whose path has
+/// no or a misleading extension.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+];
+
+/// The content of a blob.
+pub struct Raw {
+    /// The bytes of the blob.
+    pub content: Vec<u8>,
+    /// Media type sniffed from the content and the path of the blob.
+    pub content_type: String,
+}
+
+/// Read the blob at `path` in `revision`, which is looked up in the namespace of `reference`.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If there is no blob at `path` in the revision.
+///   * [`Error::BlobTooLarge`] if the blob exceeds [`MAX_SIZE`].
+pub fn raw(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    path: &str,
+) -> Result<Raw, Error> {
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        let entry = match repo.find_commit(head)?.tree()?.get_path(Path::new(path)) {
+            Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => entry,
+            Ok(_) => return Err(Error::PathNotFound(path.to_string())),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Err(Error::PathNotFound(path.to_string()))
+            },
+            Err(err) => return Err(err.into()),
+        };
+        // Only the header is read, so that large blobs are rejected before they're loaded.
+        let (size, _) = repo.odb()?.read_header(entry.id())?;
+        if size as u64 > MAX_SIZE {
+            return Err(Error::BlobTooLarge(MAX_SIZE));
+        }
+        let blob = repo.find_blob(entry.id())?;
+
+        Ok(Raw {
+            content_type: content_type(path, &blob),
+            content: blob.content().to_vec(),
+        })
+    })
+}
+
+/// Determine the media type of `blob`. Known file signatures take precedence over the extension
+/// of `path`, content that is neither is served as plain text or an octet stream.
+///
+/// HTML and XML are served as plain text, so that they are never rendered as documents in the
+/// context of the API.
+fn content_type(path: &str, blob: &git2::Blob) -> String {
+    let content = blob.content();
+    if let Some((_, media_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| content.starts_with(signature))
+    {
+        return (*media_type).to_string();
+    }
+
+    match mime_guess::from_path(path).first() {
+        Some(guess) if guess.subtype() == mime::HTML || guess.subtype() == mime::XML => {
+            PLAIN_TEXT.to_string()
+        },
+        Some(guess) if guess.type_() == mime::TEXT => {
+            format!("{}; charset=utf-8", guess.essence_str())
+        },
+        Some(guess) if blob.is_binary() || guess.subtype() == mime::SVG => {
+            guess.essence_str().to_string()
+        },
+        _ if blob.is_binary() => OCTET_STREAM.to_string(),
+        _ => PLAIN_TEXT.to_string(),
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Find and [`render`] the README of a directory.
+
+use std::path::{Component, Path, PathBuf};
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use crate::{browser, error::Error};
+
+/// Extensions of READMEs rendered as markdown, in order of preference.
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd", "mkdn"];
+
+/// A rendered README.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readme {
+    /// Path of the README blob.
+    pub path: String,
+    /// Id of the README blob.
+    pub sha1: Oid,
+    /// The README rendered to sanitized HTML.
+    pub html: String,
+}
+
+/// A relative URL found in a README, resolved to a path in the tree of the revision.
+#[derive(Debug, PartialEq)]
+pub enum Target<'a> {
+    /// A link to a blob.
+    Blob(&'a str),
+    /// A link to a directory.
+    Tree(&'a str),
+    /// An image, which is embedded with its raw content.
+    Image(&'a str),
+}
+
+/// Find the README in the directory `prefix` of `revision` and render it to sanitized HTML. The
+/// revision is looked up in the namespace of `reference`. Markdown READMEs are rendered, all
+/// others are shown as preformatted text.
+///
+/// Relative URLs of links and images are passed to `rewrite` as a [`Target`], the URL it returns
+/// is used instead.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If there is no README in the directory.
+pub fn render<F>(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    prefix: &str,
+    rewrite: F,
+) -> Result<Readme, Error>
+where
+    F: Fn(Target) -> String + Send,
+{
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+    let prefix = prefix.trim_matches('/');
+
+    browser::using_repository(peer, |repo| {
+        let root = repo.find_commit(head)?.tree()?;
+        let tree = if prefix.is_empty() {
+            root.clone()
+        } else {
+            match root.get_path(Path::new(prefix)) {
+                Ok(entry) if entry.kind() == Some(git2::ObjectType::Tree) => {
+                    repo.find_tree(entry.id())?
+                },
+                Ok(_) => return Err(Error::PathNotFound(prefix.to_string())),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                    return Err(Error::PathNotFound(prefix.to_string()))
+                },
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let (name, oid) = find(&tree).ok_or_else(|| {
+            Error::PathNotFound(Path::new(prefix).join("README").display().to_string())
+        })?;
+        let blob = repo.find_blob(oid)?;
+        let content = String::from_utf8_lossy(blob.content());
+
+        let html = if is_markdown(&name) {
+            let resolve_url = |url: &str, image: bool| {
+                let (path, fragment) = match url.find('#') {
+                    Some(index) => url.split_at(index),
+                    None => (url, ""),
+                };
+                let path = resolve(prefix, path)?;
+                let target = if image {
+                    Target::Image(&path)
+                } else {
+                    match root.get_path(Path::new(&path)).map(|entry| entry.kind()) {
+                        Ok(Some(git2::ObjectType::Tree)) => Target::Tree(&path),
+                        _ => Target::Blob(&path),
+                    }
+                };
+                Some(format!("{}{}", rewrite(target), fragment))
+            };
+            markdown(&content, resolve_url)
+        } else {
+            format!("<pre>{}</pre>", ammonia::clean_text(&content))
+        };
+
+        Ok(Readme {
+            path: if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            },
+            sha1: oid.into(),
+            html,
+        })
+    })
+}
+
+/// Find the README blob in `tree`, ignoring case. Markdown READMEs are preferred.
+fn find(tree: &git2::Tree) -> Option<(String, git2::Oid)> {
+    tree.iter()
+        .filter(|entry| entry.kind() == Some(git2::ObjectType::Blob))
+        .filter_map(|entry| {
+            let name = entry.name()?.to_string();
+            let lowercase = name.to_lowercase();
+            let rank = match lowercase.strip_prefix("readme")? {
+                "" => MARKDOWN_EXTENSIONS.len() + 1,
+                extension => match extension.strip_prefix('.') {
+                    Some(extension) => MARKDOWN_EXTENSIONS
+                        .iter()
+                        .position(|markdown| *markdown == extension)
+                        .unwrap_or(MARKDOWN_EXTENSIONS.len()),
+                    None => return None,
+                },
+            };
+            Some((rank, name, entry.id()))
+        })
+        .min_by(|(a, a_name, _), (b, b_name, _)| a.cmp(b).then_with(|| a_name.cmp(b_name)))
+        .map(|(_, name, oid)| (name, oid))
+}
+
+/// Returns `true` if the README `name` has a markdown extension.
+fn is_markdown(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| {
+            MARKDOWN_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+/// Render `content` as markdown to sanitized HTML. The URLs of links and images are passed to
+/// `resolve` together with a flag that is set for images, relative URLs are replaced with what it
+/// returns.
+fn markdown<F>(content: &str, resolve: F) -> String
+where
+    F: Fn(&str, bool) -> Option<String>,
+{
+    let parser = Parser::new_ext(content, Options::all()).map(|event| match event {
+        Event::Start(Tag::Link(kind, url, title)) => {
+            Event::Start(Tag::Link(kind, rewrite(url, false, &resolve), title))
+        },
+        Event::Start(Tag::Image(kind, url, title)) => {
+            Event::Start(Tag::Image(kind, rewrite(url, true, &resolve), title))
+        },
+        event => event,
+    });
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::clean(&unsafe_html)
+}
+
+/// Replace `url` with what `resolve` returns for it, if it is relative.
+fn rewrite<'a, F>(url: CowStr<'a>, image: bool, resolve: &F) -> CowStr<'a>
+where
+    F: Fn(&str, bool) -> Option<String>,
+{
+    if is_relative(&url) {
+        if let Some(rewritten) = resolve(&url, image) {
+            return rewritten.into();
+        }
+    }
+    url
+}
+
+/// Returns `true` for URLs that point into the repository rather than to another site or an
+/// anchor of the README itself.
+fn is_relative(url: &str) -> bool {
+    !url.is_empty()
+        && !url.starts_with('#')
+        && !url.starts_with("//")
+        && matches!(
+            url::Url::parse(url),
+            Err(url::ParseError::RelativeUrlWithoutBase)
+        )
+}
+
+/// Resolve the relative `url` found in a README in the directory `prefix` to a path in the tree.
+/// URLs starting with `/` are relative to the root of the tree. Returns `None` for URLs leaving
+/// the tree.
+fn resolve(prefix: &str, url: &str) -> Option<String> {
+    let url = url.split('?').next().unwrap_or_default();
+    let url = percent_encoding::percent_decode_str(url).decode_utf8_lossy();
+
+    let mut path = PathBuf::new();
+    if !url.starts_with('/') {
+        path.push(prefix);
+    }
+    for component in Path::new(url.as_ref()).components() {
+        match component {
+            Component::Normal(component) => path.push(component),
+            Component::ParentDir => {
+                if !path.pop() {
+                    return None;
+                }
+            },
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {},
+        }
+    }
+
+    path.to_str().map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resolve() {
+        assert_eq!(
+            super::resolve("", "docs/intro.md"),
+            Some("docs/intro.md".to_string())
+        );
+        assert_eq!(
+            super::resolve("src", "./lib.rs"),
+            Some("src/lib.rs".to_string())
+        );
+        assert_eq!(
+            super::resolve("src/bin", "../lib.rs"),
+            Some("src/lib.rs".to_string())
+        );
+        assert_eq!(
+            super::resolve("src", "/README.md"),
+            Some("README.md".to_string())
+        );
+        assert_eq!(
+            super::resolve("src", "a%20b.md?raw=1"),
+            Some("src/a b.md".to_string())
+        );
+        assert_eq!(super::resolve("", "../outside.md"), None);
+    }
+
+    #[test]
+    fn markdown() {
+        let html = super::markdown(
+            "[docs](docs/intro.md) [site](https://radicle.xyz) [top](#top)\n\n\
+             ![logo](img/logo.png)\n\n<script>alert(1)</script>",
+            |url, image| {
+                let endpoint = if image { "raw" } else { "blob" };
+                Some(format!("http://localhost/{}/{}", endpoint, url))
+            },
+        );
+
+        assert!(html.contains(r#"href="http://localhost/blob/docs/intro.md""#));
+        assert!(html.contains(r#"href="https://radicle.xyz""#));
+        assert!(html.contains(r##"href="#top""##));
+        assert!(html.contains(r#"src="http://localhost/raw/img/logo.png""#));
+        assert!(!html.contains("<script>"));
+    }
+}
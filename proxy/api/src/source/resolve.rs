@@ -0,0 +1,422 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`resolve`] revisions as found in links and typed by users into the commits they refer to.
+
+use std::str::FromStr as _;
+
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// Minimum number of hex digits of an abbreviated commit id, as in git.
+const MIN_ABBREVIATION: usize = 4;
+
+/// Number of hex digits of a full commit id.
+const FULL_SHA: usize = 40;
+
+/// The outcome of resolving a revision.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Resolution {
+    /// All matches of the revision refer to the same commit, the most specific one is given.
+    Resolved(Candidate),
+    /// The revision matches different commits.
+    Ambiguous {
+        /// All matches, the ones of the local peer first.
+        candidates: Vec<Candidate>,
+    },
+}
+
+/// A commit a revision refers to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    /// Id of the commit.
+    pub sha1: Oid,
+    /// The peer in whose namespace the revision was found, `None` for the local peer.
+    pub peer_id: Option<PeerId>,
+    /// What the revision matched.
+    pub kind: Kind,
+    /// Name of the branch or tag, `None` if the revision matched the commit id.
+    pub name: Option<String>,
+}
+
+/// The kinds of things a revision can match, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Kind {
+    /// A tag.
+    Tag,
+    /// A branch.
+    Branch,
+    /// The id of the commit, possibly abbreviated.
+    Commit,
+}
+
+/// A revision as understood by [`resolve`].
+#[derive(Debug, PartialEq)]
+enum Query {
+    /// A commit id, possibly abbreviated.
+    Sha(String),
+    /// A branch of a single peer, `None` for the local peer.
+    Branch {
+        /// The peer owning the branch.
+        peer_id: Option<PeerId>,
+        /// Name of the branch.
+        name: String,
+    },
+    /// A tag of any peer.
+    Tag(String),
+    /// A branch or tag of any peer, or a commit id if the name is made of hex digits.
+    Name(String),
+}
+
+impl Query {
+    /// Parse `rev`, which is one of
+    ///   * a [`radicle_source::Revision`] serialised as JSON,
+    ///   * `<peer_id>/<branch>` for a branch of a peer,
+    ///   * a branch or tag name or a possibly abbreviated commit id.
+    ///
+    /// The `local` peer is referred to by `None`.
+    fn parse(rev: &str, local: PeerId) -> Result<Self, Error> {
+        let rev = rev.trim();
+        if rev.is_empty() {
+            return Err(Error::InvalidRevision(rev.to_string()));
+        }
+
+        if rev.starts_with('{') {
+            let revision = serde_json::from_str::<radicle_source::Revision<PeerId>>(rev)
+                .map_err(|_| Error::InvalidRevision(rev.to_string()))?;
+            return Ok(match revision {
+                radicle_source::Revision::Branch { name, peer_id } => Self::Branch {
+                    peer_id: peer_id.filter(|peer_id| *peer_id != local),
+                    name,
+                },
+                radicle_source::Revision::Tag { name } => Self::Tag(name),
+                radicle_source::Revision::Sha { sha } => Self::Sha(sha.to_string()),
+            });
+        }
+
+        if let Some((peer_id, name)) = rev.split_once('/') {
+            if let Ok(peer_id) = PeerId::from_str(peer_id) {
+                return Ok(Self::Branch {
+                    peer_id: Some(peer_id).filter(|peer_id| *peer_id != local),
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::Name(rev.to_string()))
+    }
+}
+
+/// A branch or tag in the namespace of a project.
+struct Reference {
+    /// The peer that published the reference, `None` for the local peer.
+    peer_id: Option<PeerId>,
+    /// Whether it is a branch or a tag.
+    kind: Kind,
+    /// Name of the branch or tag.
+    name: String,
+    /// The commit the reference points to.
+    target: git2::Oid,
+}
+
+impl From<&Reference> for Candidate {
+    fn from(reference: &Reference) -> Self {
+        Self {
+            sha1: reference.target.into(),
+            peer_id: reference.peer_id,
+            kind: reference.kind,
+            name: Some(reference.name.clone()),
+        }
+    }
+}
+
+/// Resolve `rev` to the commit it refers to in the namespace of the project `urn`, see
+/// [`Query::parse`] for the accepted forms.
+///
+/// Names are looked up among the branches and tags of all peers, commit ids among the commits
+/// reachable from them. If the matches refer to different commits, all of them are returned as
+/// [`Resolution::Ambiguous`].
+///
+/// # Errors
+///   * If `rev` can't be parsed.
+///   * If nothing matches `rev`.
+///   * If the references of the project could not be read or their history not be walked.
+pub fn resolve(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    rev: &str,
+) -> Result<Resolution, Error> {
+    let query = Query::parse(rev, peer.peer_id())?;
+
+    browser::using_repository(peer, |repo| {
+        let references = references(repo, urn)?;
+        let named = |kind: Option<Kind>, peer_id: Option<Option<PeerId>>, name: &str| {
+            references
+                .iter()
+                .filter(|reference| kind.map_or(true, |kind| reference.kind == kind))
+                .filter(|reference| peer_id.map_or(true, |peer_id| reference.peer_id == peer_id))
+                .filter(|reference| reference.name == name)
+                .map(Candidate::from)
+                .collect::<Vec<_>>()
+        };
+
+        let mut candidates = match &query {
+            Query::Sha(sha) => commits(repo, &references, sha)?,
+            Query::Branch { peer_id, name } => named(Some(Kind::Branch), Some(*peer_id), name),
+            Query::Tag(name) => named(Some(Kind::Tag), None, name),
+            Query::Name(name) => {
+                let mut candidates = named(None, None, name);
+                if is_sha(name) {
+                    candidates.extend(commits(repo, &references, name)?);
+                }
+                candidates
+            },
+        };
+        candidates.sort_by_key(|candidate| {
+            (
+                candidate.peer_id.map(|peer_id| peer_id.to_string()),
+                candidate.kind,
+                candidate.name.clone(),
+            )
+        });
+
+        let first = match candidates.first() {
+            Some(first) => first.clone(),
+            None => return Err(Error::RevisionNotFound(rev.to_string())),
+        };
+        if candidates
+            .iter()
+            .all(|candidate| candidate.sha1 == first.sha1)
+        {
+            Ok(Resolution::Resolved(first))
+        } else {
+            Ok(Resolution::Ambiguous { candidates })
+        }
+    })
+}
+
+/// Resolve the commit id `sha`, which may be abbreviated, to the commit it refers to. The id is
+/// looked up in the object database, like `git rev-parse` does.
+///
+/// # Errors
+///   * If `sha` is not made of 4 to 40 hex digits.
+///   * [`Error::CommitNotFound`] if no commit matches `sha`.
+///   * [`Error::AmbiguousRevision`] if git finds more than one object matching `sha`.
+pub fn commit(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    sha: &str,
+) -> Result<git2::Oid, Error> {
+    if !is_sha(sha) {
+        return Err(Error::InvalidRevision(sha.to_string()));
+    }
+    let prefix = git2::Oid::from_str(sha)?;
+
+    browser::using_repository(peer, |repo| {
+        let oid = match repo.odb()?.exists_prefix(prefix, sha.len()) {
+            Ok(oid) => oid,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Err(Error::CommitNotFound(sha.to_string()))
+            },
+            Err(err) if err.code() == git2::ErrorCode::Ambiguous => {
+                return Err(Error::AmbiguousRevision(sha.to_string()))
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        match repo.find_commit(oid) {
+            Ok(commit) => Ok(commit.id()),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                Err(Error::CommitNotFound(sha.to_string()))
+            },
+            Err(err) => Err(err.into()),
+        }
+    })
+}
+
+/// List the branches and tags of all peers in the namespace of the project `urn` that point to
+/// commits.
+fn references(repo: &git2::Repository, urn: &Urn) -> Result<Vec<Reference>, Error> {
+    let prefix = source::refs_prefix(urn, None);
+
+    let mut references = Vec::new();
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        let (peer_id, kind, name) = match reference
+            .name()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(parse_reference)
+        {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        if let Ok(target) = reference.peel_to_commit() {
+            references.push(Reference {
+                peer_id,
+                kind,
+                name,
+                target: target.id(),
+            });
+        }
+    }
+
+    Ok(references)
+}
+
+/// Find the commits whose id starts with `sha` among the commits reachable from `references`.
+/// Every commit is attributed to the first peer it is reachable from, local peer first.
+fn commits(
+    repo: &git2::Repository,
+    references: &[Reference],
+    sha: &str,
+) -> Result<Vec<Candidate>, Error> {
+    let sha = sha.to_lowercase();
+    let mut peers = references
+        .iter()
+        .map(|reference| reference.peer_id)
+        .collect::<Vec<_>>();
+    peers.sort_by_key(|peer_id| peer_id.map(|peer_id| peer_id.to_string()));
+    peers.dedup();
+
+    let mut walked = Vec::new();
+    let mut commits = Vec::new();
+    for peer_id in peers {
+        let mut revwalk = repo.revwalk()?;
+        for tip in &walked {
+            revwalk.hide(*tip)?;
+        }
+        let tips = references
+            .iter()
+            .filter(|reference| reference.peer_id == peer_id)
+            .map(|reference| reference.target)
+            .collect::<Vec<_>>();
+        for tip in &tips {
+            revwalk.push(*tip)?;
+        }
+
+        for oid in revwalk {
+            let oid = oid?;
+            if oid.to_string().starts_with(&sha) {
+                commits.push(Candidate {
+                    sha1: oid.into(),
+                    peer_id,
+                    kind: Kind::Commit,
+                    name: None,
+                });
+            }
+        }
+        walked.extend(tips);
+    }
+
+    Ok(commits)
+}
+
+/// Parse a reference relative to the `refs/` of a project namespace into the peer that published
+/// it, its kind and its name. Returns `None` for references that are neither branches nor tags.
+fn parse_reference(name: &str) -> Option<(Option<PeerId>, Kind, String)> {
+    let (peer_id, name) = match name.strip_prefix("remotes/") {
+        Some(remote) => {
+            let (peer_id, rest) = remote.split_at(remote.find('/')?);
+            (Some(PeerId::from_str(peer_id).ok()?), &rest[1..])
+        },
+        None => (None, name),
+    };
+
+    if let Some(name) = name.strip_prefix("heads/") {
+        Some((peer_id, Kind::Branch, name.to_string()))
+    } else {
+        name.strip_prefix("tags/")
+            .map(|name| (peer_id, Kind::Tag, name.to_string()))
+    }
+}
+
+/// Returns `true` if `rev` could be a possibly abbreviated commit id.
+fn is_sha(rev: &str) -> bool {
+    (MIN_ABBREVIATION..=FULL_SHA).contains(&rev.len()) && rev.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod test {
+    use link_crypto::{PeerId, SecretKey};
+    use pretty_assertions::assert_eq;
+
+    use super::{Kind, Query};
+
+    #[test]
+    fn parse_query() {
+        let local = PeerId::from(SecretKey::new());
+        let remote = PeerId::from(SecretKey::new());
+        let branch = |peer_id: Option<PeerId>, name: &str| Query::Branch {
+            peer_id,
+            name: name.to_string(),
+        };
+
+        assert_eq!(
+            Query::parse("1e0206d", local).unwrap(),
+            Query::Name("1e0206d".to_string())
+        );
+        assert_eq!(
+            Query::parse(&format!("{}/feature/x", remote), local).unwrap(),
+            branch(Some(remote), "feature/x")
+        );
+        assert_eq!(
+            Query::parse(&format!("{}/master", local), local).unwrap(),
+            branch(None, "master")
+        );
+        assert_eq!(
+            Query::parse("feature/x", local).unwrap(),
+            Query::Name("feature/x".to_string())
+        );
+        assert_eq!(
+            Query::parse(
+                &format!(
+                    r#"{{"type": "branch", "name": "dev", "peerId": "{}"}}"#,
+                    remote
+                ),
+                local
+            )
+            .unwrap(),
+            branch(Some(remote), "dev")
+        );
+        assert_eq!(
+            Query::parse(r#"{"type": "tag", "name": "v0.1.0"}"#, local).unwrap(),
+            Query::Tag("v0.1.0".to_string())
+        );
+        assert!(Query::parse(r#"{"type": "bogus"}"#, local).is_err());
+        assert!(Query::parse(" ", local).is_err());
+    }
+
+    #[test]
+    fn parse_reference() {
+        let peer_id = PeerId::from(SecretKey::new());
+
+        assert_eq!(
+            super::parse_reference("heads/feature/x"),
+            Some((None, Kind::Branch, "feature/x".to_string()))
+        );
+        assert_eq!(
+            super::parse_reference(&format!("remotes/{}/tags/v0.1.0", peer_id)),
+            Some((Some(peer_id), Kind::Tag, "v0.1.0".to_string()))
+        );
+        assert_eq!(super::parse_reference("rad/id"), None);
+        assert_eq!(super::parse_reference("remotes/not-a-peer/heads/x"), None);
+    }
+
+    #[test]
+    fn is_sha() {
+        assert!(super::is_sha("1e0206d"));
+        assert!(super::is_sha("1E0206DA8571CA71C51C91154E2FEE376E09B4E7"));
+        assert!(!super::is_sha("1e0"));
+        assert!(!super::is_sha("master"));
+    }
+}
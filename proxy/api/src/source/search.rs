@@ -0,0 +1,206 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`search`] the blobs of a revision for lines matching a pattern.
+
+use std::{ops::Range, path::Path};
+
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::{browser, error::Error};
+
+/// Number of matching lines returned if the query doesn't set a limit.
+pub const DEFAULT_LIMIT: usize = 100;
+/// Upper bound for the number of matching lines a single search returns.
+pub const MAX_LIMIT: usize = 1000;
+/// Number of lines before and after a match included as context.
+const CONTEXT_LINES: usize = 2;
+/// Blobs larger than this are skipped, they are most likely generated or vendored.
+const MAX_BLOB_SIZE: usize = 1024 * 1024;
+
+/// What to search for and where.
+#[derive(Debug, Default)]
+pub struct Query {
+    /// The pattern to search for.
+    pub pattern: String,
+    /// Interpret [`Query::pattern`] as a regular expression instead of a literal string.
+    pub regex: bool,
+    /// Only match lines where the case of the letters matches [`Query::pattern`].
+    pub case_sensitive: bool,
+    /// Only search the blobs below this path.
+    pub path: Option<String>,
+    /// Maximum number of matching lines to return, [`DEFAULT_LIMIT`] if `None`. Capped at
+    /// [`MAX_LIMIT`].
+    pub limit: Option<usize>,
+}
+
+impl Query {
+    /// Compile the pattern of the query.
+    fn matcher(&self) -> Result<Regex, Error> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+
+        Ok(RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()?)
+    }
+}
+
+/// The result of a [`search`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Search {
+    /// Commit the revision resolved to.
+    pub revision: Oid,
+    /// Blobs with at least one matching line, sorted by path.
+    pub files: Vec<File>,
+    /// `true` if the search stopped at the limit and there are more matches.
+    pub truncated: bool,
+}
+
+/// A blob containing matches.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    /// Path of the blob.
+    pub path: String,
+    /// The matching lines of the blob.
+    pub matches: Vec<Match>,
+}
+
+/// A line matching the pattern.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Match {
+    /// Number of the line, starting at 1.
+    pub line_number: usize,
+    /// Content of the line.
+    pub line: String,
+    /// Byte ranges of [`Match::line`] that matched the pattern. Empty matches are left out.
+    pub ranges: Vec<Range<usize>>,
+    /// Lines preceding the match.
+    pub before: Vec<String>,
+    /// Lines following the match.
+    pub after: Vec<String>,
+}
+
+/// Search the blobs of `revision` for lines matching `query`. The revision is looked up in the
+/// namespace of `reference`. Binary blobs and blobs larger than 1 MiB are skipped.
+///
+/// # Errors
+///   * If the pattern of the query is not a valid regular expression.
+///   * If the revision could not be resolved.
+///   * If there is no tree at the path of the query.
+pub fn search(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    query: &Query,
+) -> Result<Search, Error> {
+    let matcher = query.matcher()?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+
+    browser::using_repository(peer, |repo| {
+        let root = repo.find_commit(head)?.tree()?;
+        let (tree, prefix) = match query.path.as_deref().map(|path| path.trim_matches('/')) {
+            None | Some("") => (root, String::new()),
+            Some(path) => match root.get_path(Path::new(path)) {
+                Ok(entry) if entry.kind() == Some(git2::ObjectType::Tree) => {
+                    (repo.find_tree(entry.id())?, format!("{}/", path))
+                },
+                Ok(_) => return Err(Error::PathNotFound(path.to_string())),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                    return Err(Error::PathNotFound(path.to_string()))
+                },
+                Err(err) => return Err(err.into()),
+            },
+        };
+
+        let mut blobs = Vec::new();
+        tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    blobs.push((format!("{}{}{}", prefix, dir, name), entry.id()));
+                }
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+        blobs.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut files = Vec::new();
+        let mut count = 0;
+        let mut truncated = false;
+        for (path, oid) in blobs {
+            let blob = repo.find_blob(oid)?;
+            if blob.is_binary() || blob.size() > MAX_BLOB_SIZE {
+                continue;
+            }
+            let content = match std::str::from_utf8(blob.content()) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+
+            let lines = content.lines().collect::<Vec<_>>();
+            let mut matches = Vec::new();
+            for (index, line) in lines.iter().enumerate() {
+                let ranges = matcher
+                    .find_iter(line)
+                    .map(|found| found.range())
+                    .filter(|range| !range.is_empty())
+                    .collect::<Vec<_>>();
+                if ranges.is_empty() {
+                    continue;
+                }
+                if count == limit {
+                    truncated = true;
+                    break;
+                }
+
+                let before = index.saturating_sub(CONTEXT_LINES);
+                let after = (index + 1 + CONTEXT_LINES).min(lines.len());
+                matches.push(Match {
+                    line_number: index + 1,
+                    line: (*line).to_string(),
+                    ranges,
+                    before: lines[before..index]
+                        .iter()
+                        .map(|l| (*l).to_string())
+                        .collect(),
+                    after: lines[index + 1..after]
+                        .iter()
+                        .map(|l| (*l).to_string())
+                        .collect(),
+                });
+                count += 1;
+            }
+
+            if !matches.is_empty() {
+                files.push(File { path, matches });
+            }
+            if truncated {
+                break;
+            }
+        }
+
+        Ok(Search {
+            revision: head.into(),
+            files,
+            truncated,
+        })
+    })
+}
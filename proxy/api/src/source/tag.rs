@@ -0,0 +1,149 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`list`] the tags of a peer or [`get`] the details of a single tag.
+
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::{commit::Header, surf::vcs::git::git2};
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// A tag of a peer.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    /// Name of the tag, without the `refs/tags/` prefix.
+    pub name: String,
+    /// Commit the tag points to.
+    pub target: Oid,
+    /// Details of annotated tags, `None` for lightweight tags.
+    pub annotation: Option<Annotation>,
+}
+
+/// Details of an annotated tag.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    /// Who created the tag and when.
+    pub tagger: Option<Tagger>,
+    /// Message attached to the tag.
+    pub message: Option<String>,
+}
+
+/// Creator of an annotated tag.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tagger {
+    /// Name of the tagger.
+    pub name: String,
+    /// Email of the tagger.
+    pub email: String,
+    /// Time the tag was created, in seconds since the epoch.
+    pub time: i64,
+}
+
+/// A [`Tag`] with the header of the commit it points to.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Detail {
+    /// The tag itself.
+    #[serde(flatten)]
+    pub tag: Tag,
+    /// Header of [`Tag::target`].
+    pub commit: Header,
+}
+
+/// List the tags `peer_id` published for the project `urn`, sorted by name. `None` lists the tags
+/// of the local peer.
+///
+/// # Errors
+///   * If the references of the project could not be read.
+pub fn list(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    peer_id: Option<PeerId>,
+) -> Result<Vec<Tag>, Error> {
+    let prefix = format!("{}tags/", source::refs_prefix(urn, peer_id));
+
+    browser::using_repository(peer, |repo| {
+        let mut tags = Vec::new();
+        for reference in repo.references_glob(&format!("{}*", prefix))? {
+            let reference = reference?;
+            let name = match reference.name().and_then(|name| name.strip_prefix(&prefix)) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if let Some(tag) = tag(repo, name, &reference)? {
+                tags.push(tag);
+            }
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(tags)
+    })
+}
+
+/// Get the tag `name` that `peer_id` published for the project `urn`. `None` looks up the tag of
+/// the local peer.
+///
+/// # Errors
+///   * If there is no tag `name` pointing to a commit.
+///   * If the references of the project could not be read.
+pub fn get(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    urn: &Urn,
+    peer_id: Option<PeerId>,
+    name: &str,
+) -> Result<Detail, Error> {
+    let reference_name = format!("{}tags/{}", source::refs_prefix(urn, peer_id), name);
+
+    browser::using_repository(peer, |repo| {
+        let reference = match repo.find_reference(&reference_name) {
+            Ok(reference) => reference,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Err(Error::TagNotFound(name.to_string()))
+            },
+            Err(err) => return Err(err.into()),
+        };
+        let tag = tag(repo, name.to_string(), &reference)?
+            .ok_or_else(|| Error::TagNotFound(name.to_string()))?;
+        let commit = source::header(repo.find_commit(*tag.target)?)?;
+
+        Ok(Detail { tag, commit })
+    })
+}
+
+/// Read the tag `name` behind `reference`. Returns `None` if the tag doesn't point to a commit.
+fn tag(
+    repo: &git2::Repository,
+    name: String,
+    reference: &git2::Reference,
+) -> Result<Option<Tag>, Error> {
+    let target = match reference.peel_to_commit() {
+        Ok(commit) => commit.id(),
+        Err(_) => return Ok(None),
+    };
+    let annotation = reference
+        .target()
+        .and_then(|oid| repo.find_tag(oid).ok())
+        .map(|tag| Annotation {
+            tagger: tag.tagger().map(|tagger| Tagger {
+                name: tagger.name().unwrap_or_default().to_string(),
+                email: tagger.email().unwrap_or_default().to_string(),
+                time: tagger.when().seconds(),
+            }),
+            message: tag.message().map(ToString::to_string),
+        });
+
+    Ok(Some(Tag {
+        name,
+        target: target.into(),
+        annotation,
+    }))
+}
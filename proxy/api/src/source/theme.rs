@@ -0,0 +1,188 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! Registry of syntax highlighting themes, built-in ones and user provided `.tmTheme` files.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use lazy_static::lazy_static;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+use syntect::{
+    highlighting::{Theme, ThemeSet},
+    html::highlighted_html_for_string,
+};
+
+use super::SYNTAXES;
+use crate::{cache::Cache, error::Error};
+
+lazy_static! {
+    /// Highlighted HTML by the id of the blob, its extension and the theme. Only blobs of up to
+    /// [`MAX_CACHED_SIZE`] bytes are cached, which bounds the cache to about 256 times the
+    /// rendered HTML of such a blob.
+    static ref HIGHLIGHTED: Cache<(git2::Oid, String, String), Option<String>> = Cache::new(256);
+}
+
+/// Size in bytes of the largest content whose highlighted HTML is cached.
+const MAX_CACHED_SIZE: usize = 64 * 1024;
+
+/// Names of the themes that were available before the registry existed and the built-in themes
+/// they stand for. syntect ships no `h4x0r` theme, so it falls back to a dark one.
+const ALIASES: &[(&str, &str)] = &[
+    ("dark", "base16-ocean.dark"),
+    ("light", "base16-ocean.light"),
+    ("h4x0r", "base16-eighties.dark"),
+];
+
+/// Where a theme comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Origin {
+    /// The theme ships with syntect.
+    Builtin,
+    /// The theme was loaded from the themes directory of the user.
+    User,
+}
+
+/// Description of a registered theme.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    /// Name to pass as the `highlight` parameter of the blob endpoint.
+    pub name: String,
+    /// Where the theme comes from.
+    pub origin: Origin,
+    /// `true` if the theme has a dark background.
+    pub dark: bool,
+}
+
+/// Registered themes by name.
+pub struct Registry {
+    /// The themes with their origin.
+    themes: BTreeMap<String, (Origin, Theme)>,
+}
+
+impl Registry {
+    /// Load the built-in themes and all `.tmTheme` files in `dir`. User themes replace built-in
+    /// themes of the same name. Files that fail to load are skipped.
+    #[must_use]
+    pub fn load(dir: &Path) -> Self {
+        let mut themes = ThemeSet::load_defaults()
+            .themes
+            .into_iter()
+            .map(|(name, theme)| (name, (Origin::Builtin, theme)))
+            .collect::<BTreeMap<_, _>>();
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Self { themes },
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("tmTheme") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            match ThemeSet::get_theme(&path) {
+                Ok(theme) => {
+                    themes.insert(name, (Origin::User, theme));
+                },
+                Err(err) => {
+                    tracing::warn!(path = ?path, ?err, "failed to load syntax highlighting theme");
+                },
+            }
+        }
+
+        Self { themes }
+    }
+
+    /// Look up the theme `name`, which may also be one of the [`ALIASES`].
+    ///
+    /// # Errors
+    ///   * If `name` is neither a registered theme nor an alias.
+    fn get(&self, name: &str) -> Result<&Theme, Error> {
+        let theme = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(name, |(_, theme)| theme);
+
+        self.themes
+            .get(theme)
+            .map(|(_, theme)| theme)
+            .ok_or_else(|| Error::ThemeNotFound(name.to_string()))
+    }
+
+    /// List all registered themes, sorted by name.
+    #[must_use]
+    pub fn list(&self) -> Vec<Info> {
+        self.themes
+            .iter()
+            .map(|(name, (origin, theme))| Info {
+                name: name.clone(),
+                origin: *origin,
+                dark: is_dark(theme),
+            })
+            .collect()
+    }
+
+    /// Highlight `content` of the blob at `path` with the theme `name` and render it to HTML.
+    /// Returns `None` if there is no syntax definition for the extension of `path`. Results are
+    /// memoised by the id of the blob if `content` isn't larger than [`MAX_CACHED_SIZE`].
+    ///
+    /// # Errors
+    ///   * If there is no theme `name`.
+    pub fn highlight(
+        &self,
+        content: &str,
+        path: &str,
+        name: &str,
+    ) -> Result<Option<String>, Error> {
+        let theme = self.get(name)?;
+        let extension = match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some(extension) => extension,
+            None => return Ok(None),
+        };
+        let render = || {
+            let syntax = SYNTAXES.find_syntax_by_extension(extension);
+            Ok(syntax.map(|syntax| highlighted_html_for_string(content, &SYNTAXES, syntax, theme)))
+        };
+        if content.len() > MAX_CACHED_SIZE {
+            return render();
+        }
+        let oid = git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes())?;
+
+        HIGHLIGHTED.get_or_try_insert_with((oid, extension.to_string(), name.to_string()), render)
+    }
+}
+
+/// Returns `true` if the background of `theme` is dark.
+fn is_dark(theme: &Theme) -> bool {
+    theme.settings.background.map_or(false, |color| {
+        let luma =
+            0.299 * f64::from(color.r) + 0.587 * f64::from(color.g) + 0.114 * f64::from(color.b);
+        luma < 128.0
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use syntect::highlighting::ThemeSet;
+
+    #[test]
+    fn aliases() {
+        let themes = ThemeSet::load_defaults().themes;
+        for (alias, theme) in super::ALIASES {
+            assert!(themes.contains_key(*theme), "{} -> {}", alias, theme);
+        }
+    }
+}
@@ -0,0 +1,570 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! List a [`tree`] or describe a single [`entry`], telling submodules and symlinks apart from
+//! blobs.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr as _,
+    sync::Arc,
+};
+
+use lazy_static::lazy_static;
+use librad::git::types::{Reference, Single};
+use link_crypto::{BoxedSigner, PeerId};
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::{commit::Header, surf::vcs::git::git2};
+use serde::Serialize;
+
+use crate::{browser, cache::Cache, error::Error, source};
+
+lazy_static! {
+    /// Trees by the commit and the prefix they were listed for.
+    static ref TREES: Cache<(git2::Oid, Option<String>), Arc<Tree>> = Cache::new(256);
+}
+
+/// Name of the file mapping the paths of submodules to their URLs.
+const MODULES_FILE: &str = ".gitmodules";
+
+/// The entries of a tree, see [`radicle_source::Tree`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tree {
+    /// Path of the tree, empty for the root.
+    pub path: String,
+    /// The entries in the order of [`radicle_source::tree`], with submodules after the trees.
+    pub entries: Vec<Entry>,
+    /// The tree itself.
+    pub info: Info,
+}
+
+/// An entry of a [`Tree`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    /// Path of the entry from the root.
+    pub path: String,
+    /// What the entry is.
+    pub info: Info,
+}
+
+/// Description of an [`Entry`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    /// Last component of the path.
+    pub name: String,
+    /// The kind of the entry.
+    pub object_type: ObjectType,
+    /// The last commit that changed the entry.
+    pub last_commit: Option<Header>,
+    /// Details of submodules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<Submodule>,
+    /// Details of symlinks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink: Option<Symlink>,
+}
+
+/// The kinds of entries of a [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ObjectType {
+    /// A directory.
+    Tree,
+    /// A file.
+    Blob,
+    /// A commit of another repository.
+    Submodule,
+    /// A symbolic link.
+    Symlink,
+}
+
+/// A commit of another repository pinned in a tree.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Submodule {
+    /// The pinned commit.
+    pub commit: Oid,
+    /// URL of the repository as given in `.gitmodules`, `None` if the submodule is not listed
+    /// there.
+    pub url: Option<String>,
+    /// The project [`Submodule::url`] refers to, if it is a `rad` URL of a project that exists
+    /// locally. Set by [`link_project`].
+    pub project: Option<Urn>,
+}
+
+/// A symbolic link.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Symlink {
+    /// The target as stored in the link.
+    pub target: String,
+    /// Path of the target from the root, `None` if the target is absolute, outside of the tree or
+    /// doesn't exist.
+    pub resolved: Option<String>,
+}
+
+/// List the entries of the tree at `prefix` in `revision`, looked up in the namespace of
+/// `reference`, see [`radicle_source::tree`]. Results are memoised by the commit the revision
+/// resolves to and shared with the cache, use [`Arc::make_mut`] to change them.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If the tree could not be listed, e.g. because there is nothing at `prefix`.
+pub fn tree(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    prefix: Option<String>,
+) -> Result<Arc<Tree>, Error> {
+    let head = browser::using(peer, reference.clone(), |browser| {
+        browser::resolve(browser, revision.clone())
+    })?;
+
+    TREES.get_or_try_insert_with((head, prefix.clone()), || {
+        let listing = browser::using(peer, reference, |browser| {
+            radicle_source::tree(browser, revision, prefix)
+        })?;
+
+        browser::using_repository(peer, |repo| annotate(repo, head, listing)).map(Arc::new)
+    })
+}
+
+/// Describe the entry at `path` in `revision`, looked up in the namespace of `reference`, if it
+/// is a submodule or a symlink. Returns `None` for all other entries and if there is no entry at
+/// `path`.
+///
+/// # Errors
+///   * If the revision could not be resolved.
+///   * If the entry or the history of `path` could not be read.
+pub fn entry(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    reference: Reference<Single>,
+    revision: Option<radicle_source::Revision<PeerId>>,
+    path: &str,
+) -> Result<Option<Entry>, Error> {
+    let head = browser::using(peer, reference, |browser| {
+        browser::resolve(browser, revision)
+    })?;
+    let path = path.trim_matches('/');
+
+    browser::using_repository(peer, |repo| {
+        let root = repo.find_commit(head)?.tree()?;
+        let entry = match root.get_path(Path::new(path)) {
+            Ok(entry) => entry,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if !is_submodule(&entry) && !is_symlink(&entry) {
+            return Ok(None);
+        }
+
+        let modules = modules(repo, &root)?;
+        let last_commit = last_commits(repo, head, vec![path])?.remove(path);
+
+        Ok(Some(Entry {
+            info: describe(repo, &root, &modules, path, &entry, last_commit)?,
+            path: path.to_string(),
+        }))
+    })
+}
+
+/// Set [`Submodule::project`] if `info` describes a submodule with a `rad` URL of a project that
+/// exists locally.
+///
+/// # Errors
+///   * If the project could not be looked up.
+pub async fn link_project(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    info: &mut Info,
+) -> Result<(), Error> {
+    let submodule = match &mut info.submodule {
+        Some(submodule) => submodule,
+        None => return Ok(()),
+    };
+    let urn = match submodule.url.as_deref().and_then(project_urn) {
+        Some(urn) => urn,
+        None => return Ok(()),
+    };
+
+    if radicle_daemon::state::get_project(peer, urn.clone())
+        .await?
+        .is_some()
+    {
+        submodule.project = Some(urn);
+    }
+
+    Ok(())
+}
+
+/// List the projects the submodules at or directly below `path` in the commit `head` link to, see
+/// [`link_project`]. Returns `None` if there are no submodules there. Cheaper than [`tree`] and
+/// [`entry`], as the history of the entries isn't read.
+///
+/// # Errors
+///   * If the tree of `head` or its `.gitmodules` could not be read.
+///   * If the projects could not be looked up.
+pub async fn links(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    head: git2::Oid,
+    path: &str,
+) -> Result<Option<Vec<Urn>>, Error> {
+    let path = path.trim_matches('/');
+    let urls = browser::using_repository(peer, |repo| {
+        let root = repo.find_commit(head)?.tree()?;
+        let mut paths = Vec::new();
+        let dir = if path.is_empty() {
+            Some(root.clone())
+        } else {
+            match root.get_path(Path::new(path)) {
+                Ok(entry) if is_submodule(&entry) => {
+                    paths.push(path.to_string());
+                    None
+                },
+                Ok(entry) if entry.kind() == Some(git2::ObjectType::Tree) => {
+                    Some(entry.to_object(repo)?.peel_to_tree()?)
+                },
+                _ => None,
+            }
+        };
+        if let Some(dir) = dir {
+            for entry in dir.iter().filter(is_submodule) {
+                if let Some(name) = entry.name() {
+                    paths.push(if path.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{}/{}", path, name)
+                    });
+                }
+            }
+        }
+        if paths.is_empty() {
+            return Ok(None);
+        }
+
+        let modules = modules(repo, &root)?;
+        Ok(Some(
+            paths
+                .iter()
+                .filter_map(|path| modules.get(path).cloned())
+                .collect::<Vec<_>>(),
+        ))
+    })?;
+    let urls = match urls {
+        Some(urls) => urls,
+        None => return Ok(None),
+    };
+
+    let mut linked = Vec::new();
+    for urn in urls.iter().filter_map(|url| project_urn(url)) {
+        if radicle_daemon::state::get_project(peer, urn.clone())
+            .await?
+            .is_some()
+        {
+            linked.push(urn);
+        }
+    }
+
+    Ok(Some(linked))
+}
+
+/// Replace the entries of `listing`, the tree of the commit `head`, with their [`Entry`] and add
+/// the submodules [`radicle_source::tree`] leaves out.
+fn annotate(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    listing: radicle_source::Tree,
+) -> Result<Tree, Error> {
+    let root = repo.find_commit(head)?.tree()?;
+    let dir_path = listing.path.trim_matches('/').to_string();
+    let dir = if dir_path.is_empty() {
+        repo.find_tree(root.id())?
+    } else {
+        root.get_path(Path::new(&dir_path))?
+            .to_object(repo)?
+            .peel_to_tree()?
+    };
+    let modules = modules(repo, &root)?;
+
+    let mut entries = Vec::with_capacity(listing.entries.len());
+    for entry in listing.entries {
+        let tree_entry = match dir.get_name(&entry.info.name) {
+            Some(tree_entry) => tree_entry,
+            None => continue,
+        };
+        let info = describe(
+            repo,
+            &root,
+            &modules,
+            &entry.path,
+            &tree_entry,
+            entry.info.last_commit,
+        )?;
+        entries.push(Entry {
+            path: entry.path,
+            info,
+        });
+    }
+
+    let listed = entries
+        .iter()
+        .map(|entry| entry.info.name.clone())
+        .collect::<HashSet<_>>();
+    let unlisted = dir
+        .iter()
+        .filter(is_submodule)
+        .filter_map(|tree_entry| {
+            let name = tree_entry.name().filter(|name| !listed.contains(*name))?;
+            let path = if dir_path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", dir_path, name)
+            };
+            Some((path, tree_entry))
+        })
+        .collect::<Vec<_>>();
+    let mut last_commits = last_commits(
+        repo,
+        head,
+        unlisted.iter().map(|(path, _)| path.as_str()).collect(),
+    )?;
+    let mut submodules = Vec::with_capacity(unlisted.len());
+    for (path, tree_entry) in unlisted {
+        let last_commit = last_commits.remove(&path);
+        submodules.push(Entry {
+            info: describe(repo, &root, &modules, &path, &tree_entry, last_commit)?,
+            path,
+        });
+    }
+    let trees = entries
+        .iter()
+        .take_while(|entry| entry.info.object_type == ObjectType::Tree)
+        .count();
+    entries.splice(trees..trees, submodules);
+
+    Ok(Tree {
+        path: listing.path,
+        entries,
+        info: Info {
+            name: listing.info.name,
+            object_type: ObjectType::Tree,
+            last_commit: listing.info.last_commit,
+            submodule: None,
+            symlink: None,
+        },
+    })
+}
+
+/// Describe `entry` found at `path` in the tree `root`.
+fn describe(
+    repo: &git2::Repository,
+    root: &git2::Tree,
+    modules: &HashMap<String, String>,
+    path: &str,
+    entry: &git2::TreeEntry,
+    last_commit: Option<Header>,
+) -> Result<Info, Error> {
+    let mut info = Info {
+        name: entry.name().unwrap_or_default().to_string(),
+        object_type: ObjectType::Blob,
+        last_commit,
+        submodule: None,
+        symlink: None,
+    };
+
+    if is_submodule(entry) {
+        info.object_type = ObjectType::Submodule;
+        info.submodule = Some(Submodule {
+            commit: entry.id().into(),
+            url: modules.get(path).cloned(),
+            project: None,
+        });
+    } else if is_symlink(entry) {
+        let target = String::from_utf8_lossy(repo.find_blob(entry.id())?.content()).to_string();
+        let resolved = resolve_link(path, &target)
+            .filter(|resolved| resolved.is_empty() || root.get_path(Path::new(resolved)).is_ok());
+        info.object_type = ObjectType::Symlink;
+        info.symlink = Some(Symlink { target, resolved });
+    } else if entry.kind() == Some(git2::ObjectType::Tree) {
+        info.object_type = ObjectType::Tree;
+    }
+
+    Ok(info)
+}
+
+/// Returns `true` if `entry` is a gitlink, i.e. pins a commit of a submodule.
+fn is_submodule(entry: &git2::TreeEntry) -> bool {
+    entry.filemode() == i32::from(git2::FileMode::Commit)
+}
+
+/// Returns `true` if `entry` is a symbolic link.
+fn is_symlink(entry: &git2::TreeEntry) -> bool {
+    entry.filemode() == i32::from(git2::FileMode::Link)
+}
+
+/// Find the last commits in the history of `head` that changed the entries at `paths`, by their
+/// path. The history is walked once for all of them and only as far as the oldest of them.
+fn last_commits(
+    repo: &git2::Repository,
+    head: git2::Oid,
+    mut paths: Vec<&str>,
+) -> Result<HashMap<String, Header>, Error> {
+    let mut last_commits = HashMap::with_capacity(paths.len());
+    if paths.is_empty() {
+        return Ok(last_commits);
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push(head)?;
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let mut touched = Vec::new();
+        for path in &paths {
+            if source::history::touches(&commit, Path::new(path))? {
+                touched.push(*path);
+            }
+        }
+        if touched.is_empty() {
+            continue;
+        }
+
+        let header = source::header(commit)?;
+        paths.retain(|path| !touched.contains(path));
+        for path in touched {
+            last_commits.insert(path.to_string(), header.clone());
+        }
+        if paths.is_empty() {
+            break;
+        }
+    }
+
+    Ok(last_commits)
+}
+
+/// Read the URLs of the submodules by their path from the `.gitmodules` file of `root`.
+fn modules(repo: &git2::Repository, root: &git2::Tree) -> Result<HashMap<String, String>, Error> {
+    match root.get_name(MODULES_FILE) {
+        Some(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {
+            let blob = repo.find_blob(entry.id())?;
+            Ok(parse_modules(&String::from_utf8_lossy(blob.content())))
+        },
+        _ => Ok(HashMap::new()),
+    }
+}
+
+/// Parse the `content` of a `.gitmodules` file into the URLs of the submodules by their path.
+/// Submodules missing either are left out.
+fn parse_modules(content: &str) -> HashMap<String, String> {
+    let mut modules = HashMap::new();
+    let mut path = None;
+    let mut url = None;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            if let (Some(path), Some(url)) = (path.take(), url.take()) {
+                modules.insert(path, url);
+            }
+            continue;
+        }
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "path" => path = Some(value.trim_matches('/').to_string()),
+                "url" => url = Some(value),
+                _ => {},
+            }
+        }
+    }
+    if let (Some(path), Some(url)) = (path, url) {
+        modules.insert(path, url);
+    }
+
+    modules
+}
+
+/// Resolve the `target` of the symlink at `path` to a path from the root. Returns `None` if the
+/// target is absolute or leaves the tree.
+fn resolve_link(path: &str, target: &str) -> Option<String> {
+    if target.starts_with('/') {
+        return None;
+    }
+
+    let mut components = path.split('/').collect::<Vec<_>>();
+    components.pop();
+    for component in target.split('/') {
+        match component {
+            "" | "." => {},
+            ".." => {
+                components.pop()?;
+            },
+            component => components.push(component),
+        }
+    }
+
+    Some(components.join("/"))
+}
+
+/// Parse the URL of a submodule into the URN of the project it refers to, either given as the URN
+/// itself or as a `rad://` remote URL.
+fn project_urn(url: &str) -> Option<Urn> {
+    Urn::from_str(url)
+        .ok()
+        .or_else(|| source::working_copy::urn_from_url(url))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_modules() {
+        let modules = super::parse_modules(
+            "[submodule \"vendor/lib\"]\n\
+             \tpath = vendor/lib\n\
+             \turl = https://example.com/lib.git\n\
+             # no url\n\
+             [submodule \"broken\"]\n\
+             \tpath = broken\n\
+             [submodule \"radicle\"]\n\
+             \turl = \"rad:git:hnrkyghsrokxzxpy9pww69xr11dr9q7edbxfo\"\n\
+             \tpath = deps/radicle/\n",
+        );
+
+        assert_eq!(modules.len(), 2);
+        assert_eq!(
+            modules.get("vendor/lib").map(String::as_str),
+            Some("https://example.com/lib.git")
+        );
+        assert_eq!(
+            modules.get("deps/radicle").map(String::as_str),
+            Some("rad:git:hnrkyghsrokxzxpy9pww69xr11dr9q7edbxfo")
+        );
+    }
+
+    #[test]
+    fn resolve_link() {
+        assert_eq!(
+            super::resolve_link("docs/latest", "v2"),
+            Some("docs/v2".to_string())
+        );
+        assert_eq!(
+            super::resolve_link("bin/tool", "../src/./tool.sh"),
+            Some("src/tool.sh".to_string())
+        );
+        assert_eq!(super::resolve_link("root", "."), Some("".to_string()));
+        assert_eq!(super::resolve_link("escape", "../outside"), None);
+        assert_eq!(super::resolve_link("absolute", "/etc/passwd"), None);
+    }
+}
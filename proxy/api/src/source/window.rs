@@ -0,0 +1,162 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! [`cut`] a [`Window`] of lines out of the content of a blob.
+
+use serde::Serialize;
+
+/// A [`radicle_source::blob::Blob`] of which only a window of lines may have been returned.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blob {
+    /// The blob, its content limited to the [`Window`].
+    #[serde(flatten)]
+    pub blob: radicle_source::blob::Blob,
+    /// The lines of text blobs that were returned, `None` for binary blobs.
+    #[serde(flatten)]
+    pub window: Option<Window>,
+}
+
+/// The lines of a text blob that were returned.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Window {
+    /// Number of the first line returned, starting at 1.
+    pub start_line: usize,
+    /// Number of the last line returned, smaller than [`Window::start_line`] if no line was
+    /// returned. The last line may be cut short if it alone exceeds the byte budget.
+    pub end_line: usize,
+    /// Size of the whole blob in bytes.
+    pub total_bytes: usize,
+    /// Number of lines of the whole blob.
+    pub total_lines: usize,
+    /// `true` if not all of the blob was returned.
+    pub truncated: bool,
+}
+
+/// Cut the lines `start_line` to `end_line` out of `content`, both inclusive and starting at 1.
+/// Lines are added until the window is complete or has `max_bytes`. A first line longer than
+/// `max_bytes` is cut short rather than left out.
+///
+/// Without bounds the window reaches from the first to the last line, without `max_bytes` it
+/// isn't limited in size.
+#[must_use]
+pub fn cut(
+    content: &str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    max_bytes: Option<usize>,
+) -> (String, Window) {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let total_lines = lines.len();
+    let start_line = start_line.unwrap_or(1).max(1);
+    let last_line = end_line.map_or(total_lines, |end_line| end_line.min(total_lines));
+    let max_bytes = max_bytes.unwrap_or(usize::MAX);
+
+    let mut window = String::new();
+    let mut end_line = start_line - 1;
+    let mut cut_short = false;
+    for line in lines.iter().take(last_line).skip(start_line - 1) {
+        if window.len() + line.len() > max_bytes {
+            if window.is_empty() {
+                window.push_str(truncate(line, max_bytes));
+                end_line += 1;
+                cut_short = true;
+            }
+            break;
+        }
+        window.push_str(line);
+        end_line += 1;
+    }
+
+    let truncated = cut_short || start_line > 1 || end_line < total_lines;
+
+    (
+        window,
+        Window {
+            start_line,
+            end_line,
+            total_bytes: content.len(),
+            total_lines,
+            truncated,
+        },
+    )
+}
+
+/// Cut `line` to at most `max_bytes`, without splitting a character.
+fn truncate(line: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    line.get(..end).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::Window;
+
+    const CONTENT: &str = "one\ntwo\nthree\nfour";
+
+    fn window(start_line: usize, end_line: usize, truncated: bool) -> Window {
+        Window {
+            start_line,
+            end_line,
+            total_bytes: CONTENT.len(),
+            total_lines: 4,
+            truncated,
+        }
+    }
+
+    #[test]
+    fn cut() {
+        assert_eq!(
+            super::cut(CONTENT, None, None, None),
+            (CONTENT.to_string(), window(1, 4, false))
+        );
+        assert_eq!(
+            super::cut(CONTENT, Some(2), Some(3), None),
+            ("two\nthree\n".to_string(), window(2, 3, true))
+        );
+        assert_eq!(
+            super::cut(CONTENT, Some(3), Some(10), None),
+            ("three\nfour".to_string(), window(3, 4, true))
+        );
+        assert_eq!(
+            super::cut(CONTENT, Some(5), None, None),
+            (String::new(), window(5, 4, true))
+        );
+    }
+
+    #[test]
+    fn cut_max_bytes() {
+        assert_eq!(
+            super::cut(CONTENT, None, None, Some(9)),
+            ("one\ntwo\n".to_string(), window(1, 2, true))
+        );
+        // A line longer than the budget is cut short.
+        assert_eq!(
+            super::cut(CONTENT, Some(3), None, Some(3)),
+            ("thr".to_string(), window(3, 3, true))
+        );
+        assert_eq!(
+            super::cut("añb", None, None, Some(2)),
+            (
+                "a".to_string(),
+                Window {
+                    start_line: 1,
+                    end_line: 1,
+                    total_bytes: 4,
+                    total_lines: 1,
+                    truncated: true,
+                }
+            )
+        );
+    }
+}
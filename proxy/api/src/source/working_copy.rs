@@ -0,0 +1,237 @@
+// Copyright © 2021 The Radicle Upstream Contributors
+//
+// This file is part of radicle-upstream, distributed under the GPLv3
+// with Radicle Linking Exception. For full terms see the included
+// LICENSE file.
+
+//! The [`status`] of a working copy on disk and how it relates to the monorepo.
+
+use std::{path::Path, str::FromStr as _};
+
+use link_crypto::BoxedSigner;
+use link_identities::git::Urn;
+use radicle_git_ext::Oid;
+use radicle_source::surf::vcs::git::git2;
+use serde::Serialize;
+
+use crate::{browser, error::Error, source};
+
+/// Name of the remote pointing to the monorepo in working copies of projects.
+const RAD_REMOTE: &str = "rad";
+
+/// The state of a working copy.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    /// Names of all local branches, sorted.
+    pub branches: Vec<String>,
+    /// The checked out branch, `None` if `HEAD` is detached.
+    pub head: Option<String>,
+    /// `true` if there are uncommitted changes to tracked files.
+    pub dirty: bool,
+    /// Uncommitted changes to tracked files, staged or not, sorted by path.
+    pub changes: Vec<Change>,
+    /// Paths of untracked files, sorted.
+    pub untracked: Vec<String>,
+    /// The `rad` remote, `None` if the working copy isn't a checkout of a project.
+    pub rad: Option<Rad>,
+}
+
+/// An uncommitted change to a tracked file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// Path of the file.
+    pub path: String,
+    /// What happened to the file.
+    pub kind: ChangeKind,
+}
+
+/// The kinds of [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// The file was added to the index.
+    Added,
+    /// The file was deleted.
+    Deleted,
+    /// The content of the file changed.
+    Modified,
+    /// The file was renamed.
+    Renamed,
+    /// The file changed its type, e.g. from a regular file to a symlink.
+    TypeChange,
+    /// The file has merge conflicts.
+    Conflicted,
+}
+
+/// The `rad` remote of a working copy.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rad {
+    /// The project the remote points to.
+    pub urn: Urn,
+    /// Tip of the checked out branch as published by the local peer in the monorepo, `None` if
+    /// the branch wasn't published yet.
+    pub published: Option<Oid>,
+    /// Number of commits on the checked out branch that are not published.
+    ///
+    /// `None` if the counts can't be determined, because nothing is checked out or the
+    /// published tip wasn't fetched into the working copy yet.
+    pub ahead: Option<usize>,
+    /// Number of published commits missing from the checked out branch. `None` under the same
+    /// conditions as [`Rad::ahead`].
+    pub behind: Option<usize>,
+}
+
+/// Get the status of the working copy at `path`. If the working copy has a `rad` remote, the
+/// checked out branch is compared with the branch of the same name the local peer published in
+/// the monorepo.
+///
+/// # Errors
+///   * If there is no repository with a working directory at `path`.
+///   * If the repository has no branches.
+pub fn status(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    path: &Path,
+) -> Result<Status, Error> {
+    let repo = git2::Repository::open(path)?;
+
+    let mut branches = repo
+        .branches(Some(git2::BranchType::Local))?
+        .map(|branch| {
+            let (branch, _) = branch?;
+            Ok(branch.name()?.map(ToString::to_string))
+        })
+        .filter_map(Result::transpose)
+        .collect::<Result<Vec<_>, git2::Error>>()?;
+    if branches.is_empty() {
+        return Err(radicle_source::Error::NoBranches.into());
+    }
+    branches.sort();
+
+    let head = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().map(ToString::to_string),
+        Ok(_) => None,
+        Err(err) if err.code() == git2::ErrorCode::UnbornBranch => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let (changes, untracked) = changes(&repo)?;
+    let rad = rad(peer, &repo, head.as_deref())?;
+
+    Ok(Status {
+        branches,
+        head,
+        dirty: !changes.is_empty(),
+        changes,
+        untracked,
+        rad,
+    })
+}
+
+/// Collect the changes to tracked files and the untracked files of `repo`.
+fn changes(repo: &git2::Repository) -> Result<(Vec<Change>, Vec<String>), Error> {
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false)
+        .renames_head_to_index(true);
+
+    let mut changes = Vec::new();
+    let mut untracked = Vec::new();
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let path = match entry.path() {
+            Some(path) => path.to_string(),
+            None => continue,
+        };
+        let status = entry.status();
+
+        let kind = if status == git2::Status::WT_NEW {
+            untracked.push(path);
+            continue;
+        } else if status.is_conflicted() {
+            ChangeKind::Conflicted
+        } else if status.is_index_new() {
+            ChangeKind::Added
+        } else if status.is_index_deleted() || status.is_wt_deleted() {
+            ChangeKind::Deleted
+        } else if status.is_index_renamed() || status.is_wt_renamed() {
+            ChangeKind::Renamed
+        } else if status.is_index_typechange() || status.is_wt_typechange() {
+            ChangeKind::TypeChange
+        } else if status.is_index_modified() || status.is_wt_modified() {
+            ChangeKind::Modified
+        } else {
+            continue;
+        };
+        changes.push(Change { path, kind });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    untracked.sort();
+
+    Ok((changes, untracked))
+}
+
+/// Read the `rad` remote of `repo` and compare the branch `head` with its published version.
+fn rad(
+    peer: &radicle_daemon::net::peer::Peer<BoxedSigner>,
+    repo: &git2::Repository,
+    head: Option<&str>,
+) -> Result<Option<Rad>, Error> {
+    let remote = match repo.find_remote(RAD_REMOTE) {
+        Ok(remote) => remote,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let urn = match remote.url().and_then(urn_from_url) {
+        Some(urn) => urn,
+        None => return Ok(None),
+    };
+
+    let published = match head {
+        Some(head) => browser::using_repository(peer, |monorepo| {
+            let name = format!("{}heads/{}", source::refs_prefix(&urn, None), head);
+            match monorepo.refname_to_id(&name) {
+                Ok(oid) => Ok(Some(oid)),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })?,
+        None => None,
+    };
+
+    let local = head.and(repo.head().ok().and_then(|reference| reference.target()));
+    let (ahead, behind) = match (local, published) {
+        (Some(local), Some(published)) if repo.find_commit(published).is_ok() => {
+            let (ahead, behind) = repo.graph_ahead_behind(local, published)?;
+            (Some(ahead), Some(behind))
+        },
+        // Nothing was published yet, so everything on the branch is unpublished.
+        (Some(local), None) => {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(local)?;
+            (Some(revwalk.count()), Some(0))
+        },
+        _ => (None, None),
+    };
+
+    Ok(Some(Rad {
+        urn,
+        published: published.map(Oid::from),
+        ahead,
+        behind,
+    }))
+}
+
+/// Extract the project URN from the URL of a `rad` remote, e.g.
+/// `rad://hnrkyghsrokxzxpy9pww69xr11dr9q7edbxfo.git`.
+pub(super) fn urn_from_url(url: &str) -> Option<Urn> {
+    let id = url.strip_prefix("rad://")?;
+    // The URL may name a peer in front of the project.
+    let id = id.rsplit('@').next()?.trim_end_matches('/');
+    let id = id.strip_suffix(".git").unwrap_or(id);
+
+    Urn::from_str(&format!("rad:git:{}", id)).ok()
+}
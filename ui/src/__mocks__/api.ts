@@ -31,6 +31,7 @@ export const upstreamProjectMock: project.Project = {
     branches: 2,
     commits: 22,
     contributors: 222,
+    languages: [{ name: "Rust", bytes: 2048, files: 2 }],
   },
 };
 
@@ -46,6 +47,7 @@ const surfProjectMock: project.Project = {
     branches: 3,
     commits: 33,
     contributors: 333,
+    languages: [],
   },
 };
 
@@ -55,6 +57,7 @@ export const sessionMock: session.SessionData = {
       theme: Theme.Dark,
       uiFont: UIFont.Inter,
       codeFont: CodeFont.SourceCode,
+      codeTheme: null,
       hints: {
         showRemoteHelper: true,
       },
@@ -68,7 +71,11 @@ export const sessionMock: session.SessionData = {
 
 export const localStateMock: source.LocalState = {
   branches: ["main", "other-branch"],
-  managed: false,
+  head: "main",
+  dirty: false,
+  changes: [],
+  untracked: [],
+  rad: null,
 };
 
 export const get = async (endpoint: string): Promise<MockedResponse> => {
@@ -4,11 +4,14 @@
 // with Radicle Linking Exception. For full terms see the included
 // LICENSE file.
 
+import * as api from "ui/src/api";
 import type { Project } from "ui/src/project";
 import * as source from "ui/src/source";
+import type * as diff from "ui/src/source/diff";
 import * as proxy from "ui/src/proxy";
 import type * as proxyProject from "ui/src/proxy/project";
 import type { Identity } from "ui/src/proxy/identity";
+import type { CommitHeader, RevisionSelector } from "ui/src/proxy/source";
 
 export interface Patch {
   id: string;
@@ -18,9 +21,25 @@ export interface Patch {
   description: string | null;
   commit: string;
   mergeBase: string | null;
-  merged: boolean;
+  status: proxyProject.PatchStatus;
 }
 
+export interface PatchFileStats {
+  path: string;
+  previousPath: string | null;
+  additions: number;
+  deletions: number;
+}
+
+// A patch with the changes it introduces, as computed by the proxy.
+export type PatchChanges = proxyProject.Patch & {
+  // Oldest first.
+  commits: CommitHeader[];
+  diff: diff.Diff;
+  stats: { files: number; additions: number; deletions: number };
+  files: PatchFileStats[];
+};
+
 export interface PatchDetails {
   patch: Patch;
   commits: source.GroupedCommitsHistory;
@@ -45,7 +64,7 @@ function makePatch(proxyPatch: proxyProject.Patch): Patch {
     description,
     commit: proxyPatch.commit,
     mergeBase: proxyPatch.mergeBase,
-    merged: proxyPatch.mergeBase === proxyPatch.commit,
+    status: proxyPatch.status,
   };
 }
 
@@ -56,6 +75,128 @@ export const getAll = async (projectUrn: string): Promise<Patch[]> => {
   return proxyPatches.map(makePatch);
 };
 
+export const getChanges = (
+  projectUrn: string,
+  peerId: string,
+  id: string
+): Promise<PatchChanges> => {
+  return api.get<PatchChanges>(
+    `projects/${projectUrn}/patches/${peerId}/${encodeURIComponent(id)}`
+  );
+};
+
+export interface CreateParams {
+  // Branch or commit of the local peer, the default branch if omitted.
+  revision?: RevisionSelector;
+  // Derived from the title if omitted.
+  id?: string;
+  title: string;
+  description?: string;
+}
+
+export const create = (
+  projectUrn: string,
+  params: CreateParams
+): Promise<PatchChanges> => {
+  return api.post<CreateParams, PatchChanges>(
+    `projects/${projectUrn}/patches`,
+    params
+  );
+};
+
+export interface UpdateParams {
+  // Branch or commit of the local peer, the current commit is kept if omitted.
+  revision?: RevisionSelector;
+  // The current title and description are kept if omitted.
+  title?: string;
+  description?: string;
+}
+
+export const update = (
+  projectUrn: string,
+  id: string,
+  params: UpdateParams
+): Promise<PatchChanges> => {
+  return api.put<UpdateParams, PatchChanges>(
+    `projects/${projectUrn}/patches/${encodeURIComponent(id)}`,
+    params
+  );
+};
+
+// Close a patch of the local peer.
+export const close = (projectUrn: string, id: string): Promise<null> => {
+  return api.del(`projects/${projectUrn}/patches/${encodeURIComponent(id)}`);
+};
+
+export interface RangeDiffCommit {
+  status: "unchanged" | "modified" | "added" | "removed";
+  old: CommitHeader | null;
+  new: CommitHeader | null;
+  // Only present for modified commits.
+  interdiff: string | null;
+}
+
+export interface RangeDiff {
+  from: proxyProject.PatchRevision;
+  to: proxyProject.PatchRevision;
+  commits: RangeDiffCommit[];
+}
+
+// Compare two revisions of a patch, by default the last two.
+export const getRangeDiff = (
+  projectUrn: string,
+  peerId: string,
+  id: string,
+  from?: number,
+  to?: number
+): Promise<RangeDiff> => {
+  return api.get<RangeDiff>(
+    `projects/${projectUrn}/range-diff/${peerId}/${encodeURIComponent(id)}`,
+    { query: { from, to } }
+  );
+};
+
+export interface PatchComment {
+  id: string;
+  // The peer that posted the comment.
+  peerId: string;
+  // Seconds since the epoch.
+  timestamp: number;
+  body: string;
+  replyTo: string | null;
+  // Oldest first.
+  replies: PatchComment[];
+}
+
+// Get the discussion of a patch, threads oldest first.
+export const getComments = (
+  projectUrn: string,
+  peerId: string,
+  id: string
+): Promise<PatchComment[]> => {
+  return api.get<PatchComment[]>(
+    `projects/${projectUrn}/comments/${peerId}/${encodeURIComponent(id)}`
+  );
+};
+
+export interface CommentParams {
+  body: string;
+  // The comment to reply to, omit to start a new thread.
+  replyTo?: string;
+}
+
+export const postComment = (
+  projectUrn: string,
+  peerId: string,
+  id: string,
+  params: CommentParams
+): Promise<PatchComment> => {
+  return api.post<CommentParams, PatchComment>(
+    `projects/${projectUrn}/comments/${peerId}/${encodeURIComponent(id)}`,
+    params
+  );
+};
+
 export const getDetails = async (
   project: Project,
   peerId: string,
@@ -86,13 +227,13 @@ export const getDetails = async (
 // the patch head and the default branch head have a common ancestor
 // but the patch head is not a descendent of the default branch head.
 //
-// If the `patch` is `merged` the filtering is skipped and all commits
+// If the `patch` is merged the filtering is skipped and all commits
 // are listed.
 const getCommits = async (
   project: Project,
   patch: Patch
 ): Promise<source.GroupedCommitsHistory> => {
-  if (!patch.merged && patch.mergeBase) {
+  if (patch.status !== "merged" && patch.mergeBase) {
     const patchCommits = await source.fetchCommits(project.urn, patch.peerId, {
       type: source.RevisionType.Sha,
       sha: patch.commit,
@@ -41,6 +41,13 @@ const projectSchema: zod.Schema<Project> = zod.object({
     branches: zod.number(),
     commits: zod.number(),
     contributors: zod.number(),
+    languages: zod.array(
+      zod.object({
+        name: zod.string(),
+        bytes: zod.number(),
+        files: zod.number(),
+      })
+    ),
   }),
 });
 
@@ -54,10 +61,17 @@ const failedProjectSchema: zod.Schema<FailedProject> = zod.object({
   metadata: metadataSchema,
 });
 
+export interface Language {
+  name: string;
+  bytes: number;
+  files: number;
+}
+
 export interface Stats {
   branches: number;
   commits: number;
   contributors: number;
+  languages: Language[];
 }
 
 interface NewRepo {
@@ -159,12 +173,29 @@ const peerSchema: zod.Schema<Peer> = zod.object({
   ]),
 });
 
+export type PatchStatus = "open" | "merged" | "closed";
+
+export interface PatchRevision {
+  number: number;
+  commit: string;
+  message: string | null;
+}
+
+const patchRevisionSchema: zod.ZodSchema<PatchRevision> = zod.object({
+  number: zod.number(),
+  commit: zod.string(),
+  message: zod.string().nullable(),
+});
+
 export interface Patch {
   id: string;
   peer: Peer;
   message: string | null;
   commit: string;
   mergeBase: string | null;
+  status: PatchStatus;
+  // Oldest first, the last one is the current version.
+  revisions: PatchRevision[];
 }
 
 const patchSchema: zod.ZodSchema<Patch> = zod.object({
@@ -173,6 +204,44 @@ const patchSchema: zod.ZodSchema<Patch> = zod.object({
   message: zod.string().nullable(),
   commit: zod.string(),
   mergeBase: zod.string().nullable(),
+  status: zod.enum(["open", "merged", "closed"]),
+  revisions: zod.array(patchRevisionSchema),
+});
+
+export interface ActivityWeek {
+  start: number;
+  commits: number;
+}
+
+export interface ActivityAuthor {
+  name: string;
+  email: string;
+  peer: Peer | null;
+  commits: number;
+  weeks: ActivityWeek[];
+}
+
+export interface Activity {
+  head: string;
+  authors: ActivityAuthor[];
+}
+
+const activitySchema: zod.Schema<Activity> = zod.object({
+  head: zod.string(),
+  authors: zod.array(
+    zod.object({
+      name: zod.string(),
+      email: zod.string(),
+      peer: peerSchema.nullable(),
+      commits: zod.number(),
+      weeks: zod.array(
+        zod.object({
+          start: zod.number(),
+          commits: zod.number(),
+        })
+      ),
+    })
+  ),
 });
 
 export class Client {
@@ -283,6 +352,20 @@ export class Client {
     );
   }
 
+  async activity(
+    projectUrn: string,
+    options?: RequestOptions
+  ): Promise<Activity> {
+    return this.fetcher.fetchOk(
+      {
+        method: "GET",
+        path: `projects/${projectUrn}/activity`,
+        options,
+      },
+      activitySchema
+    );
+  }
+
   async peerTrack(urn: string, peerId: string): Promise<boolean> {
     return this.fetcher.fetchOk(
       {
@@ -314,11 +397,15 @@ export class Client {
     );
   }
 
-  async patchList(projectUrn: string): Promise<Patch[]> {
+  async patchList(
+    projectUrn: string,
+    status?: PatchStatus
+  ): Promise<Patch[]> {
     return this.fetcher.fetchOk(
       {
         method: "GET",
         path: `projects/${projectUrn}/patches`,
+        query: { status },
       },
       zod.array(patchSchema)
     );
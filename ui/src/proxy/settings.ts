@@ -13,6 +13,7 @@ export interface Settings {
 
 export interface Appearance {
   codeFont: CodeFont;
+  codeTheme: string | null;
   hints: {
     showRemoteHelper: boolean;
   };
@@ -43,6 +44,7 @@ export enum CodeFont {
 export const settingsSchema: zod.ZodSchema<Settings> = zod.object({
   appearance: zod.object({
     codeFont: zod.enum([CodeFont.SourceCode, CodeFont.System]),
+    codeTheme: zod.string().nullable(),
     hints: zod.object({
       showRemoteHelper: zod.boolean(),
     }),
@@ -28,12 +28,29 @@ export interface CommitHeader {
 export enum ObjectType {
   Blob = "BLOB",
   Tree = "TREE",
+  Submodule = "SUBMODULE",
+  Symlink = "SYMLINK",
+}
+
+export interface Submodule {
+  commit: string;
+  url: string | null;
+  // URN of the project the URL refers to if it exists locally.
+  project: string | null;
+}
+
+export interface Symlink {
+  target: string;
+  // Path of the target from the root if it exists in the tree.
+  resolved: string | null;
 }
 
 interface Info {
   name: string;
   objectType: ObjectType;
   lastCommit: CommitHeader;
+  submodule?: Submodule;
+  symlink?: Symlink;
 }
 
 export interface SourceObject {
@@ -45,7 +62,12 @@ const sourceObjectSchema: zod.Schema<SourceObject> = zod.object({
   path: zod.string(),
   info: zod.object({
     name: zod.string(),
-    objectType: zod.enum([ObjectType.Blob, ObjectType.Tree]),
+    objectType: zod.enum([
+      ObjectType.Blob,
+      ObjectType.Tree,
+      ObjectType.Submodule,
+      ObjectType.Symlink,
+    ]),
     lastCommit: zod.object({
       author: personSchema,
       committer: personSchema,
@@ -54,6 +76,19 @@ const sourceObjectSchema: zod.Schema<SourceObject> = zod.object({
       sha1: zod.string(),
       summary: zod.string(),
     }),
+    submodule: zod
+      .object({
+        commit: zod.string(),
+        url: zod.string().nullable(),
+        project: zod.string().nullable(),
+      })
+      .optional(),
+    symlink: zod
+      .object({
+        target: zod.string(),
+        resolved: zod.string().nullable(),
+      })
+      .optional(),
   }),
 });
 
@@ -63,16 +98,33 @@ const sourceObjectSchema: zod.Schema<SourceObject> = zod.object({
 export type Blob = SourceObject & BlobContent;
 
 type BlobContent =
-  | { binary: false; html: boolean; content: string }
-  | { binary: true };
+  | ({ binary: false; html: boolean; content: string } & BlobWindow)
+  | { binary: true }
+  // Submodules and symlinks are only described by their `info`.
+  | { binary?: undefined; content?: undefined };
+
+// The lines of a text blob that were returned.
+export interface BlobWindow {
+  startLine: number;
+  endLine: number;
+  totalBytes: number;
+  totalLines: number;
+  truncated: boolean;
+}
 
 const blobContentSchema: zod.Schema<BlobContent> = zod.union([
   zod.object({
     binary: zod.literal(false),
     html: zod.boolean(),
     content: zod.string(),
+    startLine: zod.number(),
+    endLine: zod.number(),
+    totalBytes: zod.number(),
+    totalLines: zod.number(),
+    truncated: zod.boolean(),
   }),
   zod.object({ binary: zod.literal(true) }),
+  zod.object({ binary: zod.undefined(), content: zod.undefined() }),
 ]);
 
 // We can’t explicitly annotate this with the schema type.
@@ -108,9 +160,24 @@ interface BlobGetParams {
   peerId?: string;
   path: string;
   revision: RevisionSelector;
-  highlight?: "dark" | "light" | "h4x0r";
+  highlight?: string;
+  startLine?: number;
+  endLine?: number;
+  maxBytes?: number;
 }
 
+export interface HighlightTheme {
+  name: string;
+  origin: "builtin" | "user";
+  dark: boolean;
+}
+
+const highlightThemeSchema: zod.Schema<HighlightTheme> = zod.object({
+  name: zod.string(),
+  origin: zod.enum(["builtin", "user"]),
+  dark: zod.boolean(),
+});
+
 export class Client {
   private fetcher: Fetcher;
 
@@ -128,10 +195,24 @@ export class Client {
           peerId: params.peerId,
           revision: { peerId: params.peerId, ...params.revision },
           highlight: params.highlight,
+          startLine: params.startLine,
+          endLine: params.endLine,
+          maxBytes: params.maxBytes,
         },
         options,
       },
       blobSchema
     );
   }
+
+  async themesGet(options?: RequestOptions): Promise<HighlightTheme[]> {
+    return this.fetcher.fetchOk(
+      {
+        method: "GET",
+        path: "source/themes",
+        options,
+      },
+      zod.array(highlightThemeSchema)
+    );
+  }
 }
@@ -15,6 +15,7 @@ import * as remote from "ui/src/remote";
 import * as router from "ui/src/router";
 import * as source from "ui/src/source";
 import * as appearance from "ui/src/appearance";
+import * as session from "ui/src/session";
 
 export enum ViewKind {
   Aborted = "ABORTED",
@@ -324,7 +325,8 @@ const fetchBlob = async (
     peer.peerId,
     path,
     revision,
-    get(appearance.theme),
+    // The code theme chosen in the settings, the one matching the UI theme otherwise.
+    get(session.settings).appearance.codeTheme || get(appearance.theme),
     signal
   );
   return {
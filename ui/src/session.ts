@@ -92,6 +92,7 @@ export const settings: Readable<Settings> = derived(sessionStore, sess => {
         theme: Theme.Dark,
         uiFont: UIFont.Inter,
         codeFont: CodeFont.SourceCode,
+        codeTheme: null,
         hints: {
           showRemoteHelper: true,
         },
@@ -56,6 +56,32 @@ export interface CommitsHistory {
   stats: Stats;
 }
 
+export interface GraphBranch {
+  peerId: PeerId | null;
+  name: string;
+}
+
+export type GraphNode = CommitHeader & {
+  parents: Sha1[];
+  branches: GraphBranch[];
+};
+
+export interface Graph {
+  commits: GraphNode[];
+  truncated: boolean;
+}
+
+export interface RevisionCandidate {
+  sha1: Sha1;
+  peerId: PeerId | null;
+  kind: "tag" | "branch" | "commit";
+  name: string | null;
+}
+
+export type RevisionResolution =
+  | ({ type: "resolved" } & RevisionCandidate)
+  | { type: "ambiguous"; candidates: RevisionCandidate[] };
+
 interface CommitGroup {
   time: string;
   commits: CommitHeader[];
@@ -66,9 +92,29 @@ export enum ObjectType {
   Tree = "TREE",
 }
 
+export interface WorkingCopyChange {
+  path: string;
+  kind:
+    | "added"
+    | "deleted"
+    | "modified"
+    | "renamed"
+    | "typeChange"
+    | "conflicted";
+}
+
 export interface LocalState {
   branches: string[];
-  managed: boolean;
+  head: string | null;
+  dirty: boolean;
+  changes: WorkingCopyChange[];
+  untracked: string[];
+  rad: {
+    urn: string;
+    published: Sha1 | null;
+    ahead: number | null;
+    behind: number | null;
+  } | null;
 }
 
 export interface Tree extends SourceObject {
@@ -100,7 +146,7 @@ export const fetchBlob = async (
   peerId: string,
   path: string,
   revision: RevisionSelector,
-  highlight?: "dark" | "light" | "h4x0r",
+  highlight?: string,
   signal?: AbortSignal
 ): Promise<Blob> => {
   if (isMarkdown(path)) {
@@ -137,9 +183,12 @@ export const fetchBranches = (
 
 export const fetchCommit = (
   projectUrn: string,
-  sha1: Sha1
+  sha1: Sha1,
+  peerId?: PeerId
 ): Promise<Commit> => {
-  return api.get<Commit>(`source/commit/${projectUrn}/${sha1}`);
+  return api.get<Commit>(`source/commit/${projectUrn}/${sha1}`, {
+    query: { peerId },
+  });
 };
 
 export const fetchCommits = (
@@ -161,6 +210,25 @@ export const fetchCommits = (
     });
 };
 
+export const fetchGraph = (
+  projectUrn: string,
+  peerIds?: PeerId[],
+  limit?: number
+): Promise<Graph> => {
+  return api.get<Graph>(`source/graph/${projectUrn}`, {
+    query: { peerIds, limit },
+  });
+};
+
+export const resolveRevision = (
+  projectUrn: string,
+  rev: string | RevisionSelector
+): Promise<RevisionResolution> => {
+  return api.get<RevisionResolution>(`source/resolve/${projectUrn}`, {
+    query: { rev: typeof rev === "string" ? rev : JSON.stringify(rev) },
+  });
+};
+
 export const fetchReadme = async (
   projectUrn: string,
   peerId: PeerId,
@@ -213,13 +281,13 @@ export const fetchTags = (
   peerId?: PeerId
 ): Promise<Tag[]> => {
   return api
-    .get<string[]>(`source/tags/${projectUrn}`, {
+    .get<Array<{ name: string }>>(`source/tags/${projectUrn}`, {
       query: {
         peerId,
       },
     })
-    .then(names =>
-      names.map(name => {
+    .then(tags =>
+      tags.map(({ name }) => {
         return { type: RevisionType.Tag, name };
       })
     );
@@ -244,6 +312,23 @@ export const getLocalState = (path: string): Promise<LocalState> => {
   });
 };
 
+export interface RenderedReadme {
+  path: string;
+  sha1: Sha1;
+  html: string;
+}
+
+export const fetchRenderedReadme = (
+  projectUrn: string,
+  peerId: PeerId,
+  revision: RevisionSelector,
+  prefix?: string
+): Promise<RenderedReadme> => {
+  return api.get<RenderedReadme>(`source/readme/${projectUrn}`, {
+    query: { peerId, revision: { ...revision, peerId }, prefix },
+  });
+};
+
 const findReadme = (tree: Tree): string | null => {
   for (const entry of tree.entries) {
     if (entry.info.objectType !== ObjectType.Blob) {